@@ -0,0 +1,108 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+// Compares the perfect hash `Intrinsic::find` against the `match` tables it
+// replaced, looking up every name in `tests/expected-output`. Needs the
+// default features, so that every platform is generated, and `match-tables`.
+
+#![feature(rustc_private, test)]
+#![allow(bad_style)]
+
+extern crate rustc_platform_intrinsics;
+extern crate test;
+
+use rustc_platform_intrinsics::{Intrinsic, IntrinsicDef, Type};
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::path::Path;
+use test::{black_box, Bencher};
+
+// The old tables refer to the types through the crate root.
+include!(concat!(env!("OUT_DIR"), "/types.rs"));
+
+mod old {
+    use Intrinsic;
+
+    #[allow(unused_imports)]
+    mod aarch64 {
+        include!(concat!(env!("OUT_DIR"), "/aarch64_match.rs"));
+    }
+    #[allow(unused_imports)]
+    mod arm {
+        include!(concat!(env!("OUT_DIR"), "/arm_match.rs"));
+    }
+    #[allow(unused_imports)]
+    mod hexagon {
+        include!(concat!(env!("OUT_DIR"), "/hexagon_match.rs"));
+    }
+    #[allow(unused_imports)]
+    mod nvptx {
+        include!(concat!(env!("OUT_DIR"), "/nvptx_match.rs"));
+    }
+    #[allow(unused_imports)]
+    mod x86 {
+        include!(concat!(env!("OUT_DIR"), "/x86_match.rs"));
+    }
+
+    pub fn find(name: &str) -> Option<Intrinsic> {
+        aarch64::find(name)
+            .or_else(|| arm::find(name))
+            .or_else(|| hexagon::find(name))
+            .or_else(|| nvptx::find(name))
+            .or_else(|| x86::find(name))
+    }
+}
+
+// Every full intrinsic name, e.g. `x86_mm256_abs_epi8`.
+fn names() -> Vec<String> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/expected-output");
+    let mut names = vec![];
+    for entry in fs::read_dir(dir).unwrap() {
+        let mut source = String::new();
+        File::open(entry.unwrap().path()).unwrap().read_to_string(&mut source).unwrap();
+        let prefix = source.split("starts_with(\"").nth(1).unwrap().split('"').next().unwrap();
+        for line in source.lines() {
+            let line = line.trim();
+            if line.ends_with("=> Intrinsic {") {
+                names.push(format!("{}{}", prefix, line.split('"').nth(1).unwrap()));
+            }
+        }
+    }
+    names
+}
+
+fn llvm_name(i: Option<Intrinsic>) -> Option<&'static str> {
+    i.map(|i| match i.definition {
+        IntrinsicDef::Named(name) => name,
+    })
+}
+
+#[bench]
+fn find_match(b: &mut Bencher) {
+    let names = names();
+    b.iter(|| for name in &names {
+        black_box(old::find(name));
+    });
+}
+
+#[bench]
+fn find_phf(b: &mut Bencher) {
+    let names = names();
+    for name in &names {
+        let found = llvm_name(Intrinsic::find(name));
+        assert!(found.is_some(), "`{}` not found", name);
+        assert_eq!(found, llvm_name(old::find(name)), "`{}`", name);
+        assert!(Intrinsic::find(&format!("{}_", name)).is_none());
+    }
+    b.iter(|| for name in &names {
+        black_box(Intrinsic::find(name));
+    });
+}
@@ -1,23 +1,152 @@
 extern crate intrinsic_gen;
 
 use intrinsic_gen::*;
-use std::path::{Path,PathBuf};
+use std::env;
+use std::path::PathBuf;
+
+static SETS_VAR: &'static str = "RUSTC_PLATFORM_INTRINSICS_SETS";
 
 fn main() {
     std::env::set_var("RUST_BACKTRACE", "full");
-    let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let dir = env::var("CARGO_MANIFEST_DIR").unwrap();
 
     let mut input = PathBuf::from(&dir);
     input.push("tests/input");
+    println!("cargo:rerun-if-changed={}", input.display());
+
+    // `lib.rs` includes the generated `<platform>.rs` modules from here.
+    let output = PathBuf::from(env::var("OUT_DIR").unwrap());
+
+    let mut entries: Vec<PathBuf> = input.read_dir()
+        .expect("read_dir failed")
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+
+    println!("cargo:rerun-if-env-changed={}", SETS_VAR);
+    let listed: Vec<String> = env::var(SETS_VAR)
+        .unwrap_or_default()
+        .split(',')
+        .map(|set| set.trim().to_lowercase())
+        .filter(|set| !set.is_empty())
+        .collect();
+    let mut known = vec![];
 
-    let mut output = PathBuf::from(&dir);
-    //output.push("tests/output");
+    let mut platforms = vec![];
+    let mut errors = vec![];
+    for path in entries {
+        // Each platform has a cargo feature named after its file or
+        // directory, and a platform directory one per instruction set too;
+        // disabled ones are left out of `Intrinsic::find`. Build scripts only
+        // see the enabled features, but `all` should enable every one of
+        // them, so with `all` on a disabled feature is missing from it.
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        let mut files = vec![];
+        if path.is_dir() {
+            for file in path.read_dir().expect("read_dir failed") {
+                if let Ok(file) = file {
+                    files.push(file.path());
+                }
+            }
+        } else {
+            files.push(path.clone());
+        }
+        let any_set = files.iter()
+            .filter_map(|file| file.file_stem())
+            .any(|set| enabled(&format!("{}-{}", name, set.to_string_lossy())));
+        if !enabled(&name) && !(path.is_dir() && any_set) {
+            if enabled("all") {
+                println!("cargo:warning={}: `all` doesn't enable a `{}` feature in Cargo.toml, \
+                          so the platform is left out",
+                         path.display(),
+                         name);
+            }
+            continue;
+        }
+        // Directory mtimes don't change when a file inside is edited.
+        for file in &files {
+            println!("cargo:rerun-if-changed={}", file.display());
+        }
 
-    let files = input.read_dir().expect("read_dir failed");
-    for entry in files {
-        if let Ok(entry) = entry {
-            let platform = parse(&entry.path());
-            generate(platform, &output);
+        match parse(&path) {
+            Ok(mut platform) => {
+                if path.is_dir() {
+                    for set in platform.instruction_sets() {
+                        let feature = format!("{}-{}", name, set);
+                        if enabled("all") && !enabled(&feature) {
+                            println!("cargo:warning={}: `all` doesn't enable a `{}` feature in \
+                                      Cargo.toml, so the set is only there with `{}`",
+                                     path.join(format!("{}.json", set)).display(),
+                                     feature,
+                                     name);
+                        }
+                    }
+                    select_sets(&name, &mut platform, &listed, &mut known);
+                }
+                platforms.push(platform);
+            }
+            Err(mut e) => errors.append(&mut e),
+        }
+    }
+    for platform in &platforms {
+        if let Err(mut e) = generate(platform, &output) {
+            errors.append(&mut e);
+            continue;
+        }
+        // The `match` based tables `find` used before, for `benches/find.rs`.
+        if enabled("match-tables") {
+            if let Err(mut e) = generate_format(platform, &output, "_match", &CompilerDefs) {
+                errors.append(&mut e);
+            }
+        }
+    }
+    if let Err(mut e) = generate_platforms(&platforms, &output) {
+        errors.append(&mut e);
+    }
+    // Monomorphising again would only repeat the errors reported above.
+    if errors.is_empty() {
+        if let Err(mut e) = generate_types(&platforms, &output) {
+            errors.append(&mut e);
         }
     }
+
+    for set in &listed {
+        if !known.contains(set) {
+            println!("cargo:warning={}: unknown or disabled instruction set `{}`",
+                     SETS_VAR,
+                     set);
+        }
+    }
+
+    if !errors.is_empty() {
+        for e in &errors {
+            for line in diagnostic::render(e).lines() {
+                println!("cargo:warning={}", line);
+            }
+        }
+        panic!("{} error(s) in the platform intrinsic definitions", errors.len());
+    }
 }
+
+// Chooses the instruction sets of a platform directory, e.g. `x86/avx2.json`.
+// The platform's feature enables all of them and an `x86-avx2` feature only
+// its own, so that enabling more features never removes intrinsics. Listing
+// sets like `x86-avx2` in `RUSTC_PLATFORM_INTRINSICS_SETS` then narrows the
+// enabled ones down. The enabled sets are added to `known`.
+fn select_sets(name: &str, platform: &mut Platform, listed: &[String], known: &mut Vec<String>) {
+    let all = enabled(name);
+    platform.retain_sets(|set| all || enabled(&format!("{}-{}", name, set)));
+    let sets: Vec<String> = platform.instruction_sets()
+        .iter()
+        .map(|set| format!("{}-{}", name, set))
+        .collect();
+    if sets.iter().any(|set| listed.contains(set)) {
+        platform.retain_sets(|set| listed.contains(&format!("{}-{}", name, set)));
+    }
+    known.extend(sets);
+}
+
+fn enabled(feature: &str) -> bool {
+    env::var_os(format!("CARGO_FEATURE_{}", feature.to_uppercase().replace('-', "_"))).is_some()
+}
+
@@ -0,0 +1,235 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Intrinsics loaded at runtime from JSON specs in the `tests/input` layout,
+// for trying out an instruction set without rebuilding the crate. Once
+// installed, a `Registry` is consulted by `Intrinsic::find` and
+// `Intrinsic::find_by_llvm_name` before the generated tables.
+//
+// `Intrinsic` only holds `'static` references, so the loaded names are leaked
+// once per load, and the types once per distinct type in a load; specs are
+// meant to be loaded a handful of times per process.
+
+use intrinsic_gen::{self, phf, GenError, Platform};
+use intrinsic_types::{Interner, OwnedType};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+
+use {Intrinsic, IntrinsicDef, Iter, Table, Type};
+
+static INSTALLED: AtomicPtr<Registry> = AtomicPtr::new(ptr::null_mut());
+
+// Why a `Registry` couldn't be loaded: an error in the specs, or a type the
+// generator accepts but the runtime `Type` can't represent.
+#[derive(Debug)]
+pub enum LoadError {
+    Spec(GenError),
+    Unsupported { path: PathBuf, reason: String },
+}
+
+impl LoadError {
+    pub fn path(&self) -> &Path {
+        match *self {
+            LoadError::Spec(ref e) => e.path(),
+            LoadError::Unsupported { ref path, .. } => path,
+        }
+    }
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            LoadError::Spec(ref e) => write!(f, "{}", e),
+            LoadError::Unsupported { ref path, ref reason } => {
+                write!(f, "{}: {}", path.display(), reason)
+            }
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn description(&self) -> &str {
+        match *self {
+            LoadError::Spec(_) => "invalid platform definition",
+            LoadError::Unsupported { .. } => "unsupported at runtime",
+        }
+    }
+}
+
+pub struct Registry {
+    // sorted by prefix, like `PLATFORMS`
+    platforms: Vec<Table>,
+    // the perfect hash of each platform's names, as `PhfTable` generates it
+    hashes: Vec<Option<phf::Table>>,
+}
+
+impl Registry {
+    // Loads every platform in `dir`, each either a JSON file or a directory
+    // of instruction sets with an info file, like `build.rs` does for
+    // `tests/input`.
+    pub fn load(dir: &Path) -> Result<Registry, Vec<LoadError>> {
+        let io_error = |e| {
+            vec![LoadError::Spec(GenError::Io {
+                                     path: dir.to_owned(),
+                                     error: e,
+                                 })]
+        };
+        let mut entries: Vec<PathBuf> = dir.read_dir()
+            .map_err(&io_error)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<_, _>>()
+            .map_err(&io_error)?;
+        entries.sort();
+
+        let mut platforms = vec![];
+        let mut errors = vec![];
+        for path in entries {
+            match intrinsic_gen::parse(&path) {
+                Ok(platform) => platforms.push(platform),
+                Err(e) => errors.extend(e.into_iter().map(LoadError::Spec)),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Registry::from_platforms(&platforms)
+    }
+
+    // Builds a registry from parsed platforms, e.g. after picking their
+    // instruction sets with `Platform::retain_sets`.
+    pub fn from_platforms(platforms: &[Platform]) -> Result<Registry, Vec<LoadError>> {
+        let mut interner = Interner::new();
+        let mut tables = vec![];
+        let mut errors = vec![];
+        for p in platforms {
+            match table(p, &mut interner) {
+                Ok(table) => tables.push(table),
+                Err(mut e) => errors.append(&mut e),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        // stable, so that the first of two platforms with a prefix wins
+        tables.sort_by(|a, b| (a.0).0.cmp((b.0).0));
+        let (platforms, hashes) = tables.into_iter().unzip();
+        Ok(Registry {
+            platforms: platforms,
+            hashes: hashes,
+        })
+    }
+
+    pub fn find(&self, name: &str) -> Option<Intrinsic> {
+        self.platforms
+            .iter()
+            .zip(&self.hashes)
+            .filter(|&(&(prefix, _, _), _)| name.starts_with(prefix))
+            .filter_map(|(&(_, table, _), hash)| {
+                let hash = hash.as_ref()?;
+                let i = hash.order[phf::index(name, hash.seed, &hash.disps, table.len())];
+                if table[i].0 == name { Some(table[i].1) } else { None }
+            })
+            .next()
+    }
+
+    pub fn iter(&self) -> Iter {
+        ::iter_tables(self.platforms.iter().map(|&(_, table, _)| table).collect())
+    }
+
+    pub fn iter_platform(&self, prefix: &str) -> Iter {
+        ::iter_platform(&self.platforms, prefix)
+    }
+
+    pub fn iter_prefix(&self, prefix: &str) -> Iter {
+        ::iter_prefix(&self.platforms, prefix)
+    }
+
+    pub fn find_by_llvm_name(&self, llvm_name: &str) -> Vec<&'static str> {
+        ::find_by_llvm_name(&self.platforms, llvm_name)
+    }
+
+    // Makes the `Intrinsic` lookups consult this registry first, in place of
+    // any installed before. This is meant to be called once per process: a
+    // replaced registry is never freed, as another thread may still be
+    // looking something up in it, so each further call leaks one.
+    pub fn install(self) {
+        INSTALLED.store(Box::into_raw(Box::new(self)), Ordering::Release);
+    }
+
+    pub fn installed() -> Option<&'static Registry> {
+        let registry = INSTALLED.load(Ordering::Acquire);
+        if registry.is_null() {
+            None
+        } else {
+            Some(unsafe { &*registry })
+        }
+    }
+}
+
+// The `Table` of a platform and the perfect hash of its names, as `PhfTable`
+// would generate them.
+fn table(p: &Platform,
+         interner: &mut Interner)
+         -> Result<(Table, Option<phf::Table>), Vec<LoadError>> {
+    let intrinsics = p.monomorphise()
+        .map_err(|e| e.into_iter().map(LoadError::Spec).collect::<Vec<_>>())?;
+    let names: Vec<String> =
+        intrinsics.iter().map(|m| format!("{}{}", m.platform_prefix(), m.name())).collect();
+    let keys: Vec<(&str, &str)> =
+        names.iter().zip(&intrinsics).map(|(n, m)| (&n[..], m.llvm_name())).collect();
+    let tables = phf::tables(p.path(), &keys).map_err(|e| vec![LoadError::Spec(e)])?;
+
+    let mut entries: Vec<(&'static str, Intrinsic)> = vec![];
+    for &i in &tables.entries {
+        let mut to_static = |ty: &OwnedType| {
+            interner.intern(ty).ok_or_else(|| {
+                vec![LoadError::Unsupported {
+                         path: p.path().to_owned(),
+                         reason: format!("`{}` uses `{}`, which `Type` can't represent",
+                                         names[i],
+                                         ty.rust_name()),
+                     }]
+            })
+        };
+        let m = &intrinsics[i];
+        let mut inputs: Vec<&'static Type> = vec![];
+        for arg in m.args() {
+            inputs.push(to_static(arg)?);
+        }
+        let intrinsic = Intrinsic {
+            inputs: leak(inputs.into_boxed_slice()),
+            output: to_static(m.ret())?,
+            definition: IntrinsicDef::Named(leak_str(m.llvm_name().to_string())),
+        };
+        entries.push((leak_str(names[i].clone()), intrinsic));
+    }
+    let by_llvm_name: Vec<(&'static str, u16)> = tables.by_llvm_name
+        .iter()
+        .map(|&i| {
+            let IntrinsicDef::Named(llvm_name) = entries[i as usize].1.definition;
+            (llvm_name, i)
+        })
+        .collect();
+    Ok(((leak_str(p.platform_prefix()),
+         leak(entries.into_boxed_slice()),
+         leak(by_llvm_name.into_boxed_slice())),
+        tables.hash))
+}
+
+fn leak<T: ?Sized>(b: Box<T>) -> &'static T {
+    unsafe { &*Box::into_raw(b) }
+}
+
+fn leak_str(s: String) -> &'static str {
+    leak(s.into_boxed_str())
+}
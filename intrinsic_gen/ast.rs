@@ -0,0 +1,428 @@
+use std::fmt::{self, Display, Formatter};
+
+pub use intrinsic_types::Modifier;
+
+// The type spec grammar documented in `tests/generator.py`:
+//
+//     type := core_type modifier* suffix?
+//     core_type := void | number | aggregate | reference
+//     modifier := 'v' | 'S' | 'h' | 'd' | 'n' | 'w' | 'u' | 's' | 'f' |
+//                 'D' | 'M' | 'C' | 'x' number | '.' number
+//     suffix := ('Pm' | 'Pc') ('/' type)? | '->' type
+//     void := 'V'
+//     number := ('i' | 'u' | 's' | 'f' | 'I' | 'U' | 'S' | 'F') width ('/' number)?
+//     width := number | '(' number '-' number ')'
+//     aggregate := '(' type (',' type)* ')' 'f'? | '[' type ';' number ']' 'f'?
+//     reference := number
+//
+// Lowercase number ids are vectors, uppercase ones scalars. Unlike the
+// Python generator, aggregates may nest.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spec {
+    pub offset: usize,
+    pub core: Core,
+    pub modifiers: Vec<(usize, Modifier)>,
+    pub suffix: Option<(usize, Suffix)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberId {
+    Integer,
+    Signed,
+    Unsigned,
+    Float,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Core {
+    Void,
+    Number {
+        id: NumberId,
+        vector: bool,
+        start: u32,
+        // `Some` for a `(start-end)` range
+        end: Option<u32>,
+        llvm_width: Option<u32>,
+    },
+    Reference(usize),
+    Tuple { elems: Vec<Spec>, flatten: bool },
+    Array {
+        elem: Box<Spec>,
+        count: usize,
+        flatten: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Suffix {
+    Pointer {
+        is_const: bool,
+        llvm_elem: Option<Box<Spec>>,
+    },
+    Bitcast(Box<Spec>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub offset: usize,
+    pub reason: String,
+    pub help: Option<String>,
+}
+
+const MODIFIERS: &'static str = "vShdnwusfDMC";
+
+fn modifier_from_char(c: char) -> Option<Modifier> {
+    Some(match c {
+        'v' => Modifier::Vector,
+        'S' => Modifier::Scalar,
+        'h' => Modifier::Halve,
+        'd' => Modifier::Double,
+        'n' => Modifier::Narrow,
+        'w' => Modifier::Widen,
+        'u' => Modifier::Unsigned,
+        's' => Modifier::Signed,
+        'f' => Modifier::Float,
+        'D' => Modifier::Deref,
+        'M' => Modifier::Mut,
+        'C' => Modifier::Const,
+        _ => return None,
+    })
+}
+
+impl NumberId {
+    fn from_char(c: char) -> Option<(NumberId, bool)> {
+        let id = match c.to_ascii_lowercase() {
+            'i' => NumberId::Integer,
+            's' => NumberId::Signed,
+            'u' => NumberId::Unsigned,
+            'f' => NumberId::Float,
+            _ => return None,
+        };
+        Some((id, c.is_ascii_lowercase()))
+    }
+
+    pub fn to_char(&self, vector: bool) -> char {
+        let c = match *self {
+            NumberId::Integer => 'i',
+            NumberId::Signed => 's',
+            NumberId::Unsigned => 'u',
+            NumberId::Float => 'f',
+        };
+        if vector { c } else { c.to_ascii_uppercase() }
+    }
+}
+
+pub fn parse(spec: &str) -> Result<Spec, ParseError> {
+    let mut parser = Parser {
+        src: spec,
+        pos: 0,
+    };
+    let result = parser.spec()?;
+    if parser.pos < spec.len() {
+        return Err(parser.unexpected("the end of the type spec"));
+    }
+    Ok(result)
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", c)))
+        }
+    }
+
+    fn error(&self, offset: usize, reason: String) -> ParseError {
+        ParseError {
+            offset: offset,
+            reason: reason,
+            help: None,
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> ParseError {
+        match self.peek() {
+            Some(c) => self.error(self.pos, format!("unexpected `{}`, expected {}", c, expected)),
+            None => self.error(self.pos, format!("unexpected end of spec, expected {}", expected)),
+        }
+    }
+
+    fn number(&mut self, what: &str) -> Result<u32, ParseError> {
+        let start = self.pos;
+        while self.peek().map_or(false, |c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return Err(self.unexpected(what));
+        }
+        self.src[start..self.pos]
+            .parse()
+            .map_err(|_| self.error(start, format!("{} is too large", what)))
+    }
+
+    fn spec(&mut self) -> Result<Spec, ParseError> {
+        let offset = self.pos;
+        let core = self.core()?;
+        let mut modifiers = vec![];
+        loop {
+            let at = self.pos;
+            let modifier = match self.peek() {
+                Some('.') => {
+                    self.pos += 1;
+                    Modifier::Index(self.number("an aggregate element index")? as usize)
+                }
+                Some('x') => {
+                    self.pos += 1;
+                    Modifier::ForceWidth(self.number("a vector bitwidth after `x`")?)
+                }
+                Some(c) => {
+                    match modifier_from_char(c) {
+                        Some(m) => {
+                            self.pos += 1;
+                            m
+                        }
+                        None => break,
+                    }
+                }
+                None => break,
+            };
+            modifiers.push((at, modifier));
+        }
+        let at = self.pos;
+        let suffix = if self.eat('P') {
+            let is_const = if self.eat('c') {
+                true
+            } else if self.eat('m') {
+                false
+            } else {
+                let mut e = self.unexpected("`m` or `c`");
+                e.help = Some("pointers are written `Pm` (`*mut`) or `Pc` (`*const`)".to_string());
+                return Err(e);
+            };
+            let llvm_elem = if self.eat('/') {
+                Some(Box::new(self.spec()?))
+            } else {
+                None
+            };
+            Some((at,
+                  Suffix::Pointer {
+                      is_const: is_const,
+                      llvm_elem: llvm_elem,
+                  }))
+        } else if self.src[self.pos..].starts_with("->") {
+            self.pos += 2;
+            Some((at, Suffix::Bitcast(Box::new(self.spec()?))))
+        } else {
+            None
+        };
+        if suffix.is_none() {
+            self.check_modifier()?;
+        }
+
+        Ok(Spec {
+            offset: offset,
+            core: core,
+            modifiers: modifiers,
+            suffix: suffix,
+        })
+    }
+
+    // Catches a likely typo in the modifier list, rather than reporting
+    // it as trailing garbage.
+    fn check_modifier(&self) -> Result<(), ParseError> {
+        let c = match self.peek() {
+            Some(c) if c.is_ascii_alphabetic() || c == '-' || c == '/' => c,
+            _ => return Ok(()),
+        };
+        let help = match c {
+            'q' => "did you mean `d`? it doubles the vector length".to_string(),
+            'p' => "pointers are written `Pm` (`*mut`) or `Pc` (`*const`)".to_string(),
+            'X' => "did you mean `x`, followed by a bitwidth?".to_string(),
+            '-' => "bitcasts are written `->type`".to_string(),
+            '/' => "only scalars (`I`, `U`, `S`) and pointers can have an LLVM type".to_string(),
+            c if MODIFIERS.contains(c.to_ascii_lowercase()) => {
+                format!("did you mean `{}`?", c.to_ascii_lowercase())
+            }
+            c if MODIFIERS.contains(c.to_ascii_uppercase()) => {
+                format!("did you mean `{}`?", c.to_ascii_uppercase())
+            }
+            _ => {
+                format!("modifiers are one of `{}`, `.N` or `xN`, followed by an optional `Pm`, \
+                         `Pc` or `->type` suffix",
+                        MODIFIERS)
+            }
+        };
+        let mut e = self.error(self.pos, format!("unknown modifier `{}`", c));
+        e.help = Some(help);
+        Err(e)
+    }
+
+    fn core(&mut self) -> Result<Core, ParseError> {
+        let c = match self.peek() {
+            Some(c) => c,
+            None => return Err(self.unexpected("a type")),
+        };
+        if c == 'V' {
+            self.pos += 1;
+            return Ok(Core::Void);
+        }
+        if c.is_ascii_digit() {
+            return Ok(Core::Reference(self.number("a reference")? as usize));
+        }
+        if c == '(' {
+            self.pos += 1;
+            let mut elems = vec![self.aggregate_elem()?];
+            while self.eat(',') {
+                elems.push(self.aggregate_elem()?);
+            }
+            if !self.eat(')') {
+                return Err(self.unexpected("`,` or `)`"));
+            }
+            return Ok(Core::Tuple {
+                elems: elems,
+                flatten: self.eat('f'),
+            });
+        }
+        if c == '[' {
+            self.pos += 1;
+            let elem = self.aggregate_elem()?;
+            if !self.eat(';') {
+                let mut e = self.unexpected("`;` and an element count");
+                e.help = Some("arrays are written `[type;count]`".to_string());
+                return Err(e);
+            }
+            self.skip_whitespace();
+            let count = self.number("an element count")? as usize;
+            self.skip_whitespace();
+            self.expect(']')?;
+            return Ok(Core::Array {
+                elem: Box::new(elem),
+                count: count,
+                flatten: self.eat('f'),
+            });
+        }
+        let (id, vector) = match NumberId::from_char(c) {
+            Some(id) => id,
+            None => {
+                return Err(self.unexpected("a type: `V`, a vector (`i`, `u`, `s`, `f`), a scalar \
+                                            (`I`, `U`, `S`, `F`), an aggregate or a reference"))
+            }
+        };
+        self.pos += 1;
+        let (start, end) = if self.eat('(') {
+            let start = self.number("a bitwidth")?;
+            self.expect('-')?;
+            let end = self.number("a bitwidth")?;
+            self.expect(')')?;
+            (start, Some(end))
+        } else {
+            (self.number("a bitwidth, or a range like `(8-64)`")?, None)
+        };
+        // `/` after a scalar is its LLVM width; after a pointer suffix, it
+        // introduces the LLVM pointee type.
+        let llvm_width = if end.is_none() && self.src[self.pos..].starts_with('/') &&
+                            self.src[self.pos + 1..]
+            .chars()
+            .next()
+            .map_or(false, |c| c.is_ascii_digit()) {
+            self.pos += 1;
+            Some(self.number("an LLVM bitwidth")?)
+        } else {
+            None
+        };
+        Ok(Core::Number {
+            id: id,
+            vector: vector,
+            start: start,
+            end: end,
+            llvm_width: llvm_width,
+        })
+    }
+
+    fn aggregate_elem(&mut self) -> Result<Spec, ParseError> {
+        self.skip_whitespace();
+        let elem = self.spec()?;
+        self.skip_whitespace();
+        Ok(elem)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().map_or(false, |c| c == ' ') {
+            self.pos += 1;
+        }
+    }
+}
+
+impl Display for Spec {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.core)?;
+        for &(_, ref m) in &self.modifiers {
+            write!(f, "{}", m)?;
+        }
+        match self.suffix {
+            Some((_, Suffix::Pointer { is_const, ref llvm_elem })) => {
+                write!(f, "P{}", if is_const { 'c' } else { 'm' })?;
+                if let Some(ref elem) = *llvm_elem {
+                    write!(f, "/{}", elem)?;
+                }
+            }
+            Some((_, Suffix::Bitcast(ref target))) => write!(f, "->{}", target)?,
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+impl Display for Core {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Core::Void => write!(f, "V"),
+            Core::Number { id, vector, start, end, llvm_width } => {
+                write!(f, "{}", id.to_char(vector))?;
+                match end {
+                    Some(end) => write!(f, "({}-{})", start, end)?,
+                    None => write!(f, "{}", start)?,
+                }
+                if let Some(w) = llvm_width {
+                    write!(f, "/{}", w)?;
+                }
+                Ok(())
+            }
+            Core::Reference(n) => write!(f, "{}", n),
+            Core::Tuple { ref elems, flatten } => {
+                write!(f, "(")?;
+                for (i, elem) in elems.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", elem)?;
+                }
+                write!(f, "){}", if flatten { "f" } else { "" })
+            }
+            Core::Array { ref elem, count, flatten } => {
+                write!(f, "[{};{}]{}", elem, count, if flatten { "f" } else { "" })
+            }
+        }
+    }
+}
+
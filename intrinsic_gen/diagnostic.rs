@@ -0,0 +1,166 @@
+use std::fs::File;
+use std::io::prelude::*;
+
+use error::GenError;
+
+// Renders an error the way rustc does, pointing into the JSON file:
+//
+//     error: invalid type spec `0q`: unknown modifier `q`
+//       --> tests/input/x86/avx2.json:10:24
+//        |
+//     10 |             "args": ["0q"]
+//        |                        ^ unknown modifier `q`
+//        |
+//        = help: did you mean `d`? it doubles the vector length
+//
+// Falls back to the one-line form if the file or value can't be found.
+pub fn render(error: &GenError) -> String {
+    let mut source = String::new();
+    let span = File::open(error.path())
+        .and_then(|mut f| f.read_to_string(&mut source))
+        .ok()
+        .and_then(|_| error.pointer())
+        .and_then(|pointer| locate(&source, pointer));
+    let (start, end) = match span {
+        Some(span) => span,
+        None => return format!("error: {}", error),
+    };
+
+    let (start, end, label, help) = match *error {
+        GenError::InvalidTypeSpec { offset, ref reason, ref help, .. } => {
+            // Skip the opening quote; specs never contain escapes.
+            let at = (start + 1 + offset).min(end - 1);
+            (at, at + 1, &reason[..], help.as_ref())
+        }
+        _ => (start, end, "", None),
+    };
+
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[start..].find('\n').map_or(source.len(), |i| start + i);
+    let line = &source[line_start..line_end];
+    let line_no = source[..start].matches('\n').count() + 1;
+    let column = source[line_start..start].chars().count() + 1;
+    let width = source[start..end.min(line_end)].chars().count().max(1);
+
+    let gutter = line_no.to_string().len();
+    let pad = " ".repeat(gutter);
+    let mut out = format!("error: {}\n{}--> {}:{}:{}\n{} |\n{} | {}\n{} | {}{} {}",
+                          error.message(),
+                          pad,
+                          error.path().display(),
+                          line_no,
+                          column,
+                          pad,
+                          line_no,
+                          line,
+                          pad,
+                          " ".repeat(column - 1),
+                          "^".repeat(width),
+                          label);
+    out = out.trim_end().to_string();
+    if let Some(help) = help {
+        out.push_str(&format!("\n{} |\n{} = help: {}", pad, pad, help));
+    }
+    out
+}
+
+// Finds the byte span of the value a JSON pointer (RFC 6901) refers to.
+pub fn locate(source: &str, pointer: &str) -> Option<(usize, usize)> {
+    let bytes = source.as_bytes();
+    let mut pos = skip_whitespace(bytes, 0);
+    if !pointer.is_empty() {
+        for token in pointer[1..].split('/') {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            pos = match bytes.get(pos) {
+                Some(&b'{') => find_member(bytes, pos, &token)?,
+                Some(&b'[') => find_element(bytes, pos, token.parse().ok()?)?,
+                _ => return None,
+            };
+        }
+    }
+    Some((pos, skip_value(bytes, pos)?))
+}
+
+fn find_member(bytes: &[u8], open: usize, key: &str) -> Option<usize> {
+    let mut pos = skip_whitespace(bytes, open + 1);
+    while bytes.get(pos) == Some(&b'"') {
+        let key_end = skip_value(bytes, pos)?;
+        let found = &bytes[pos + 1..key_end - 1] == key.as_bytes();
+        pos = skip_whitespace(bytes, key_end);
+        if bytes.get(pos) != Some(&b':') {
+            return None;
+        }
+        pos = skip_whitespace(bytes, pos + 1);
+        if found {
+            return Some(pos);
+        }
+        pos = skip_whitespace(bytes, skip_value(bytes, pos)?);
+        if bytes.get(pos) == Some(&b',') {
+            pos = skip_whitespace(bytes, pos + 1);
+        }
+    }
+    None
+}
+
+fn find_element(bytes: &[u8], open: usize, index: usize) -> Option<usize> {
+    let mut pos = skip_whitespace(bytes, open + 1);
+    for _ in 0..index {
+        if bytes.get(pos) == Some(&b']') {
+            return None;
+        }
+        pos = skip_whitespace(bytes, skip_value(bytes, pos)?);
+        if bytes.get(pos) != Some(&b',') {
+            return None;
+        }
+        pos = skip_whitespace(bytes, pos + 1);
+    }
+    if bytes.get(pos) == Some(&b']') {
+        None
+    } else {
+        Some(pos)
+    }
+}
+
+fn skip_whitespace(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() && (bytes[pos] as char).is_whitespace() {
+        pos += 1;
+    }
+    pos
+}
+
+// Returns the position just past the value starting at `pos`.
+fn skip_value(bytes: &[u8], pos: usize) -> Option<usize> {
+    match *bytes.get(pos)? {
+        b'"' => {
+            let mut i = pos + 1;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'\\' => i += 2,
+                    b'"' => return Some(i + 1),
+                    _ => i += 1,
+                }
+            }
+            None
+        }
+        open @ b'{' | open @ b'[' => {
+            let close = if open == b'{' { b'}' } else { b']' };
+            let mut i = skip_whitespace(bytes, pos + 1);
+            while bytes.get(i) != Some(&close) {
+                i = skip_whitespace(bytes, skip_value(bytes, i)?);
+                match bytes.get(i) {
+                    Some(&b',') | Some(&b':') => i = skip_whitespace(bytes, i + 1),
+                    Some(&c) if c == close => {}
+                    _ => return None,
+                }
+            }
+            Some(i + 1)
+        }
+        _ => {
+            let mut i = pos;
+            while i < bytes.len() && !b",:]} \t\r\n".contains(&bytes[i]) {
+                i += 1;
+            }
+            if i == pos { None } else { Some(i) }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use serde_json;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use phf;
+
+// Errors carry the file they came from and, where it makes sense, a JSON
+// pointer (RFC 6901) to the offending value, e.g. `/intrinsics/3/args/1`.
+#[derive(Debug)]
+pub enum GenError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    Json {
+        path: PathBuf,
+        error: serde_json::Error,
+    },
+    MissingPlatformInfo {
+        path: PathBuf,
+    },
+    InvalidField {
+        path: PathBuf,
+        pointer: String,
+        expected: &'static str,
+        found: String,
+    },
+    InvalidWidth {
+        path: PathBuf,
+        pointer: String,
+        width: String,
+        reason: String,
+    },
+    InvalidTypeSpec {
+        path: PathBuf,
+        pointer: String,
+        spec: String,
+        // byte offset of the offending character within `spec`
+        offset: usize,
+        reason: String,
+        help: Option<String>,
+    },
+    InvalidTemplate {
+        path: PathBuf,
+        pointer: String,
+        template: String,
+        reason: String,
+    },
+    // Two platforms whose intrinsic names can't be told apart by prefix.
+    OverlappingPlatforms {
+        path: PathBuf,
+        pointer: String,
+        prefix: String,
+        other_path: PathBuf,
+        other_prefix: String,
+    },
+    // More intrinsics than the `u16` indices of the generated tables allow.
+    TooManyIntrinsics {
+        path: PathBuf,
+        count: usize,
+    },
+}
+
+impl GenError {
+    pub fn path(&self) -> &Path {
+        match *self {
+            GenError::Io { ref path, .. } |
+            GenError::Json { ref path, .. } |
+            GenError::MissingPlatformInfo { ref path } |
+            GenError::InvalidField { ref path, .. } |
+            GenError::InvalidWidth { ref path, .. } |
+            GenError::InvalidTypeSpec { ref path, .. } |
+            GenError::InvalidTemplate { ref path, .. } |
+            GenError::OverlappingPlatforms { ref path, .. } |
+            GenError::TooManyIntrinsics { ref path, .. } => path,
+        }
+    }
+
+    pub fn pointer(&self) -> Option<&str> {
+        match *self {
+            GenError::Io { .. } |
+            GenError::Json { .. } |
+            GenError::MissingPlatformInfo { .. } |
+            GenError::TooManyIntrinsics { .. } => None,
+            GenError::InvalidField { ref pointer, .. } |
+            GenError::InvalidWidth { ref pointer, .. } |
+            GenError::InvalidTypeSpec { ref pointer, .. } |
+            GenError::InvalidTemplate { ref pointer, .. } |
+            GenError::OverlappingPlatforms { ref pointer, .. } => Some(pointer),
+        }
+    }
+
+    // The error without its location.
+    pub fn message(&self) -> String {
+        match *self {
+            GenError::Io { ref error, .. } => error.to_string(),
+            GenError::Json { ref error, .. } => format!("invalid JSON: {}", error),
+            GenError::MissingPlatformInfo { .. } => {
+                "no platform information (`platform`, `number_info`, `width_info`)".to_string()
+            }
+            GenError::InvalidField { expected, ref found, .. } => {
+                format!("expected {}, found `{}`", expected, found)
+            }
+            GenError::InvalidWidth { ref width, ref reason, .. } => {
+                format!("invalid width `{}`: {}", width, reason)
+            }
+            GenError::InvalidTypeSpec { ref spec, ref reason, .. } => {
+                format!("invalid type spec `{}`: {}", spec, reason)
+            }
+            GenError::InvalidTemplate { ref template, ref reason, .. } => {
+                format!("invalid pattern `{}`: {}", template, reason)
+            }
+            GenError::OverlappingPlatforms { ref prefix, ref other_path, ref other_prefix, .. } => {
+                format!("platform prefix `{}` overlaps with `{}` from {}",
+                        prefix,
+                        other_prefix,
+                        other_path.display())
+            }
+            GenError::TooManyIntrinsics { count, .. } => {
+                format!("{} intrinsics, more than the {} a platform can have",
+                        count,
+                        phf::MAX_KEYS)
+            }
+        }
+    }
+}
+
+impl Display for GenError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.path().display())?;
+        if let Some(pointer) = self.pointer() {
+            write!(f, ": {}", pointer)?;
+        }
+        write!(f, ": {}", self.message())
+    }
+}
+
+impl Error for GenError {
+    fn description(&self) -> &str {
+        match *self {
+            GenError::Io { .. } => "I/O error",
+            GenError::Json { .. } => "invalid JSON",
+            GenError::MissingPlatformInfo { .. } => "missing platform information",
+            GenError::InvalidField { .. } => "invalid field",
+            GenError::InvalidWidth { .. } => "invalid width",
+            GenError::InvalidTypeSpec { .. } => "invalid type spec",
+            GenError::InvalidTemplate { .. } => "invalid pattern",
+            GenError::OverlappingPlatforms { .. } => "overlapping platform prefixes",
+            GenError::TooManyIntrinsics { .. } => "too many intrinsics",
+        }
+    }
+}
@@ -2,32 +2,59 @@
 #![feature(slice_concat_ext)]
 #![feature(ascii_ctype)]
 
-#[macro_use]
-extern crate lazy_static;
-
+extern crate intrinsic_types;
 extern crate serde_json;
-extern crate regex;
 
+pub mod ast;
+pub mod diagnostic;
+pub mod error;
 pub mod parser;
+pub mod phf;
+pub mod template;
 pub mod typespec;
 
-use serde_json::Value;
+use std::collections::BTreeMap;
 use std::io::prelude::*;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::fs::File;
-use std::slice::SliceConcatExt;
-use parser::*;
 
+pub use error::GenError;
 pub use parser::*;
 
-pub fn generate(p: Platform, dir: &Path) {
-    let mut file_stem = dir.join(&p.file_stem);
-    println!("generate file name {:?}", file_stem);
-    file_stem.set_extension("rs");
+pub fn generate(p: &Platform, dir: &Path) -> Result<(), Vec<GenError>> {
+    generate_format(p, dir, "", &PhfTable)
+}
+
+// Writes `<file_stem><suffix>.rs`.
+pub fn generate_format(p: &Platform,
+                       dir: &Path,
+                       suffix: &str,
+                       format: &dyn OutputFormat)
+                       -> Result<(), Vec<GenError>> {
+    let path = dir.join(format!("{}{}.rs", p.file_stem, suffix));
+    write_file(&path, &render_format(p, format)?)
+}
 
-    let output = render(&p);
-    let mut file = File::create(file_stem).expect("create file failed");
-    file.write_all(output.as_bytes()).unwrap();
+// Writes `platforms.rs`, declaring a module for each platform's generated
+// file and the `Intrinsic::find_static` that dispatches to them.
+pub fn generate_platforms(platforms: &[Platform], dir: &Path) -> Result<(), Vec<GenError>> {
+    write_file(&dir.join("platforms.rs"), &render_platforms(platforms)?)
+}
+
+// Writes `types.rs`, the `static` types the generated modules refer to.
+pub fn generate_types(platforms: &[Platform], dir: &Path) -> Result<(), Vec<GenError>> {
+    write_file(&dir.join("types.rs"), &render_types(platforms)?)
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), Vec<GenError>> {
+    File::create(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|e| {
+            vec![GenError::Io {
+                     path: path.to_owned(),
+                     error: e,
+                 }]
+        })
 }
 
 static HEADER: &'static str = r#"
@@ -44,32 +71,299 @@ static HEADER: &'static str = r#"
 // DO NOT EDIT: autogenerated by etc/platform-intrinsics/generator.py
 // ignore-tidy-linelength
 
-#![allow(unused_imports)]
-
 use {Intrinsic, Type};
 use IntrinsicDef::Named;
 
 // The default inlining settings trigger a pathological behaviour in
 // LLVM, which causes makes compilation very slow. See #28273.
 #[inline(never)]
-pub fn find(name: &str) -> Option<Intrinsic> {
-"#;
+pub fn find(name: &str) -> Option<Intrinsic> {"#;
+
+// How `render` lays out the monomorphised intrinsics of a platform, mirroring
+// the `--format` option of `tests/generator.py`.
+pub trait OutputFormat {
+    fn open(&self, p: &Platform) -> String;
+    fn render(&self, m: &MonomorphicIntrinsic) -> String;
+    fn close(&self) -> String;
+
+    fn render_all(&self,
+                  p: &Platform,
+                  intrinsics: &[MonomorphicIntrinsic])
+                  -> Result<String, Vec<GenError>> {
+        let mut output = self.open(p);
+        for m in intrinsics {
+            output.push_str(&self.render(m));
+        }
+        output.push_str(&self.close());
+        Ok(output)
+    }
+}
 
-fn render(p: &Platform) -> String {
-    let body_start = format!(r#"
-    if !name.starts_with({0}) {{ return None }}
-    Some(match &name[{0}.len()..] {{"#,
-        p.platform_prefix()
-    );
+// The `find` table used by the compiler.
+pub struct CompilerDefs;
 
-    let body = p.generate();
+impl OutputFormat for CompilerDefs {
+    fn open(&self, p: &Platform) -> String {
+        format!(r#"{}
+    if !name.starts_with("{1}") {{ return None }}
+    Some(match &name["{1}".len()..] {{"#,
+                HEADER,
+                p.platform_prefix())
+    }
 
-    let body_end = r#"
+    fn render(&self, m: &MonomorphicIntrinsic) -> String {
+        m.to_string()
+    }
+
+    fn close(&self) -> String {
+        r#"
 
         _ => return None,
     })
 }
-"#;
+"#
+            .to_string()
+    }
+}
+
+// `extern "platform-intrinsic"` declarations, for libraries exposing the
+// intrinsics.
+pub struct ExternBlock;
 
-    [HEADER, &body_start, &body, body_end].concat()
+impl OutputFormat for ExternBlock {
+    fn open(&self, _: &Platform) -> String {
+        r#"extern "platform-intrinsic" {"#.to_string()
+    }
+
+    fn render(&self, m: &MonomorphicIntrinsic) -> String {
+        format!("\n    fn {}{}{};",
+                m.platform_prefix(),
+                m.name(),
+                m.intrinsic_signature())
+    }
+
+    fn close(&self) -> String {
+        "\n}\n".to_string()
+    }
 }
+
+// A perfect hash table with the same `find` as `CompilerDefs`, for the
+// runtime crate: a lookup hashes the name once rather than comparing it
+// against every arm of a huge `match`, which is also slow to compile.
+pub struct PhfTable;
+
+impl OutputFormat for PhfTable {
+    fn open(&self, _: &Platform) -> String {
+        format!("{}// ignore-tidy-linelength\n\nuse {{Intrinsic, Type}};\nuse \
+                 IntrinsicDef::Named;\n",
+                GENERATED_HEADER)
+    }
+
+    fn render(&self, m: &MonomorphicIntrinsic) -> String {
+        format!(r#"
+    ("{}{}", Intrinsic {{
+        inputs: {{ static INPUTS: [&'static Type; {}] = [{}]; &INPUTS }},
+        output: {},
+        definition: Named("{}")
+    }}),"#,
+                m.platform_prefix(),
+                m.name(),
+                m.args().len(),
+                m.compiler_args(),
+                m.compiler_ret(),
+                m.llvm_name())
+    }
+
+    fn close(&self) -> String {
+        "\n];\n".to_string()
+    }
+
+    fn render_all(&self,
+                  p: &Platform,
+                  intrinsics: &[MonomorphicIntrinsic])
+                  -> Result<String, Vec<GenError>> {
+        let names: Vec<String> =
+            intrinsics.iter().map(|m| format!("{}{}", m.platform_prefix(), m.name())).collect();
+        let keys: Vec<(&str, &str)> =
+            names.iter().zip(intrinsics).map(|(n, m)| (&n[..], m.llvm_name())).collect();
+        let tables = phf::tables(p.path(), &keys).map_err(|e| vec![e])?;
+
+        let mut output = self.open(p);
+        // `INTRINSICS` is sorted by name for `Intrinsic::iter_prefix`, the
+        // perfect hash maps names to its indices through `SLOTS`.
+        output.push_str(&format!("\npub static INTRINSICS: [(&'static str, Intrinsic); {}] = [",
+                                 tables.entries.len()));
+        for &i in &tables.entries {
+            output.push_str(&self.render(&intrinsics[i]));
+        }
+        output.push_str(&self.close());
+        // The indices into `INTRINSICS`, sorted by LLVM name.
+        output.push_str(&format!("\npub static BY_LLVM_NAME: [(&'static str, u16); {}] = [",
+                                 tables.by_llvm_name.len()));
+        for &i in &tables.by_llvm_name {
+            output.push_str(&format!("\n    (\"{}\", {}),",
+                                     intrinsics[tables.entries[i as usize]].llvm_name(),
+                                     i));
+        }
+        output.push_str("\n];\n");
+        let table = match tables.hash {
+            Some(table) => table,
+            None => {
+                output.push_str("\npub fn find(_: &str) -> Option<Intrinsic> {\n    None\n}\n");
+                return Ok(output);
+            }
+        };
+
+        let rows = |items: Vec<String>| {
+            items.chunks(8)
+                .map(|row| row.join(" "))
+                .collect::<Vec<String>>()
+                .join("\n    ")
+        };
+        output.push_str(&format!("\nconst SEED: u64 = {};\n\nstatic DISPS: [(u32, u32); {}] = [\n    \
+                                  {}\n];\n\nstatic SLOTS: [u16; {}] = [\n    {}\n];\n",
+                                 table.seed,
+                                 table.disps.len(),
+                                 rows(table.disps
+                                     .iter()
+                                     .map(|&(d1, d2)| format!("({}, {}),", d1, d2))
+                                     .collect()),
+                                 table.order.len(),
+                                 rows(table.order.iter().map(|i| format!("{},", i)).collect())));
+        output.push_str(&format!(r#"
+pub fn find(name: &str) -> Option<Intrinsic> {{
+    if !name.starts_with("{}") {{ return None }}
+    let (key, intrinsic) = INTRINSICS[SLOTS[phf_index(name, SEED, &DISPS, SLOTS.len())] as usize];
+    if key == name {{ Some(intrinsic) }} else {{ None }}
+}}
+
+"#,
+                                 p.platform_prefix()));
+        output.push_str(phf::LOOKUP_FNS);
+        Ok(output)
+    }
+}
+
+pub fn render(p: &Platform) -> Result<String, Vec<GenError>> {
+    render_format(p, &CompilerDefs)
+}
+
+pub fn render_format(p: &Platform, format: &dyn OutputFormat) -> Result<String, Vec<GenError>> {
+    format.render_all(p, &p.monomorphise()?)
+}
+
+pub fn render_platforms(platforms: &[Platform]) -> Result<String, Vec<GenError>> {
+    let mut infos = vec![];
+    let mut errors = vec![];
+    for p in platforms {
+        match p.platform_info() {
+            Some(info) => infos.push((&p.file_stem, info)),
+            None => errors.push(GenError::MissingPlatformInfo { path: p.path().to_owned() }),
+        }
+    }
+    // `find` dispatches on the first matching prefix, so no prefix may be a
+    // prefix of another.
+    for (i, &(_, a)) in infos.iter().enumerate() {
+        for &(_, b) in &infos[i + 1..] {
+            let (shorter, longer) = if a.name().len() <= b.name().len() { (a, b) } else { (b, a) };
+            if longer.name().starts_with(shorter.name()) {
+                errors.push(GenError::OverlappingPlatforms {
+                    path: longer.path().to_owned(),
+                    pointer: "/platform".to_string(),
+                    prefix: longer.name().to_string(),
+                    other_path: shorter.path().to_owned(),
+                    other_prefix: shorter.name().to_string(),
+                });
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut modules = String::new();
+    let mut find = String::new();
+    let mut tables = vec![];
+    for &(module, info) in &infos {
+        modules.push_str(&format!(r#"
+#[allow(unused_imports)]
+mod {0} {{
+    include!(concat!(env!("OUT_DIR"), "/{0}.rs"));
+}}
+"#,
+                                  module));
+        find.push_str(&format!(r#"if name.starts_with("{}") {{
+            {}::find(name)
+        }} else "#,
+                               info.name(),
+                               module));
+        tables.push(format!("\n    (\"{0}\", &{1}::INTRINSICS, &{1}::BY_LLVM_NAME),",
+                            info.name(),
+                            module));
+    }
+    // Sorted by prefix, so that `Intrinsic::iter` yields names in order.
+    tables.sort();
+    Ok(format!(r#"{}{}
+static PLATFORMS: [(&'static str,
+                    &'static [(&'static str, Intrinsic)],
+                    &'static [(&'static str, u16)]); {}] = [{}
+];
+
+impl Intrinsic {{
+    fn find_static({}: &str) -> Option<Intrinsic> {{
+        {}{{
+            None
+        }}
+    }}
+}}
+"#,
+               GENERATED_HEADER,
+               modules,
+               tables.len(),
+               tables.concat(),
+               // unused when every platform is disabled
+               if infos.is_empty() { "_" } else { "name" },
+               find))
+}
+
+pub fn render_types(platforms: &[Platform]) -> Result<String, Vec<GenError>> {
+    let mut statics = BTreeMap::new();
+    let mut errors = vec![];
+    for p in platforms {
+        match p.monomorphise() {
+            Ok(intrinsics) => {
+                for m in &intrinsics {
+                    m.ret().collect_statics(&mut statics);
+                    for arg in m.args() {
+                        arg.collect_statics(&mut statics);
+                    }
+                }
+            }
+            Err(mut e) => errors.append(&mut e),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut output = GENERATED_HEADER.to_string();
+    output.push('\n');
+    for (name, def) in statics {
+        output.push_str(&format!("static {}: Type = {};\n", name, def));
+    }
+    Ok(output)
+}
+
+static GENERATED_HEADER: &'static str = r#"
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// DO NOT EDIT: autogenerated by intrinsic_gen from the platform JSON files
+"#;
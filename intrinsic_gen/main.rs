@@ -0,0 +1,136 @@
+extern crate intrinsic_gen;
+
+use intrinsic_gen::{diagnostic, CompilerDefs, ExternBlock, OutputFormat, PhfTable};
+use std::env;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io;
+use std::path::PathBuf;
+use std::process;
+
+// Same command line as `tests/generator.py`:
+//
+//     intrinsic-gen --format compiler-defs -i x86/info.json x86/sse42.json
+static USAGE: &'static str = "\
+usage: intrinsic-gen [-h] --format {extern-block,compiler-defs,phf-table}
+                     [-o OUT] [-i INFO] FILE [FILE ...]
+
+Render an intrinsic definition JSON to various formats.
+
+positional arguments:
+  FILE                  JSON files to load
+
+options:
+  -h, --help            show this help message and exit
+  --format {extern-block,compiler-defs,phf-table}
+                        Output format.
+  -o OUT, --out OUT     File to output to (default stdout).
+  -i INFO, --info INFO  File containing platform specific information to merge
+                        into the input files' header.";
+
+struct Args {
+    format: String,
+    out: Option<PathBuf>,
+    info: Option<PathBuf>,
+    inputs: Vec<PathBuf>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut format = None;
+    let mut out = None;
+    let mut info = None;
+    let mut inputs = vec![];
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        // `--format=compiler-defs` is the same as `--format compiler-defs`
+        let (flag, inline) = match arg.find('=') {
+            Some(i) if arg.starts_with("--") => {
+                (arg[..i].to_string(), Some(arg[i + 1..].to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| {
+            inline.clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("argument {}: expected one argument", name))
+        };
+        match &flag[..] {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            "--format" => format = Some(value("--format")?),
+            "-o" | "--out" => out = Some(PathBuf::from(value("-o/--out")?)),
+            "-i" | "--info" => info = Some(PathBuf::from(value("-i/--info")?)),
+            f if f.starts_with('-') => return Err(format!("unrecognized argument: {}", f)),
+            _ => inputs.push(PathBuf::from(arg)),
+        }
+    }
+
+    let format = format.ok_or("the following arguments are required: --format")?;
+    if inputs.is_empty() {
+        return Err("the following arguments are required: FILE".to_string());
+    }
+    Ok(Args {
+        format: format,
+        out: out,
+        info: info,
+        inputs: inputs,
+    })
+}
+
+fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            let usage = USAGE.split("\n\n").next().unwrap();
+            let _ = writeln!(io::stderr(), "{}\nerror: {}", usage, e);
+            process::exit(2);
+        }
+    };
+    let format: &dyn OutputFormat = match &args.format[..] {
+        "compiler-defs" => &CompilerDefs,
+        "extern-block" => &ExternBlock,
+        "phf-table" => &PhfTable,
+        f => {
+            let _ = writeln!(io::stderr(),
+                             "error: argument --format: invalid choice: '{}' (choose from \
+                              'extern-block', 'compiler-defs', 'phf-table')",
+                             f);
+            process::exit(2);
+        }
+    };
+    if args.inputs.len() > 1 && args.info.is_none() {
+        let _ = writeln!(io::stderr(),
+                         "error: cannot have multiple inputs without an info header.");
+        process::exit(1);
+    }
+
+    let output = intrinsic_gen::parse_files(args.info.as_ref().map(|p| p.as_path()),
+                                            &args.inputs)
+        .and_then(|platform| intrinsic_gen::render_format(&platform, format));
+    let output = match output {
+        Ok(output) => output,
+        Err(errors) => {
+            for e in &errors {
+                let _ = writeln!(io::stderr(), "{}\n", diagnostic::render(e));
+            }
+            process::exit(1);
+        }
+    };
+
+    let written = match args.out {
+        Some(ref path) => File::create(path).and_then(|mut f| f.write_all(output.as_bytes())),
+        None => io::stdout().write_all(output.as_bytes()),
+    };
+    match written {
+        Ok(()) => {}
+        // e.g. piped into `head`, which stopped reading
+        Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe && args.out.is_none() => {}
+        Err(e) => {
+            let _ = writeln!(io::stderr(), "error: {}", e);
+            process::exit(1);
+        }
+    }
+}
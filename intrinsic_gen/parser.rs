@@ -1,79 +1,144 @@
 use serde_json::{self, Value};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::default::Default;
+use std::io;
 use std::io::prelude::*;
 use std::fs::File;
 use std::fmt::{Display, Formatter, Error};
 use std::slice::SliceConcatExt;
 use std::string::ToString;
 
+use error::GenError;
+use template;
 use typespec::Type;
-use typespec::TypeSpec;
+use typespec::{self, SpecError, TypeSpec};
 
-pub fn parse(p: &Path) -> Platform {
+pub fn parse(p: &Path) -> Result<Platform, Vec<GenError>> {
 
     if p.is_dir() {
         parse_dir(p)
     } else {
-        parse_file(p)
+        parse_file(p).map_err(|e| vec![e])
     }
 }
 
-fn parse_dir(path: &Path) -> Platform {
+// Merges several instruction sets into one platform, like `generator.py`
+// does: `info` (or else one of `files`) supplies the platform information.
+pub fn parse_files(info: Option<&Path>, files: &[PathBuf]) -> Result<Platform, Vec<GenError>> {
     let mut result = Platform::default();
-    let file_stem = path.file_stem()
-        .map(|n| n.to_owned().into_string())
-        .unwrap()
-        .unwrap();
-    println!("Parse dir {:?} , dir name {:?}", path, file_stem);
-    result.file_stem = file_stem;
-
-    if path.is_dir() {
-        let files = path.read_dir().expect(&format!("read_dir {:?} failed", path));
-        for entry in files {
-            if let Ok(entry) = entry {
-                result.merge(parse_file(&entry.path()));
+    let mut errors = vec![];
+    let mut info_platform = None;
+    for (i, file) in info.into_iter().chain(files.iter().map(|f| f.as_path())).enumerate() {
+        match parse_file(file) {
+            Ok(p) => {
+                if i == 0 && info.is_some() {
+                    info_platform = p.platform.clone();
+                }
+                result.merge(p)
             }
+            Err(e) => errors.push(e),
         }
+    }
+    // The explicit info wins over any platform information in `files`.
+    if info_platform.is_some() {
+        result.platform = info_platform;
+    }
+    if let Some(first) = files.first() {
+        result.path = first.to_owned();
+        result.file_stem = first.file_stem()
+            .map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+    }
+    if errors.is_empty() {
+        Ok(result)
     } else {
-        result.merge(parse_file(path));
+        Err(errors)
     }
-    result
 }
 
-fn parse_file(path: &Path) -> Platform {
-    let mut f = File::open(path).expect(&format!("open file {:?} failed", path));
-    let mut buffer = String::new();
-
-    let file_stem = path.file_stem()
-        .map(|n| n.to_owned().into_string())
-        .unwrap()
-        .unwrap();
-    println!("Parse file {:?} , file name {:?}", path, file_stem);
+fn parse_dir(path: &Path) -> Result<Platform, Vec<GenError>> {
+    let mut result = Platform::default();
+    result.file_stem = file_stem(path).map_err(|e| vec![e])?;
+    result.path = path.to_owned();
+
+    let io_error = |e| {
+        vec![GenError::Io {
+                 path: path.to_owned(),
+                 error: e,
+             }]
+    };
+    let mut files = vec![];
+    for entry in path.read_dir().map_err(&io_error)? {
+        files.push(entry.map_err(&io_error)?.path());
+    }
+    // `read_dir` order is platform dependent; keep the output stable.
+    files.sort();
+
+    let mut errors = vec![];
+    for file in files {
+        match parse_file(&file) {
+            Ok(p) => result.merge(p),
+            Err(e) => errors.push(e),
+        }
+    }
+    if errors.is_empty() {
+        Ok(result)
+    } else {
+        Err(errors)
+    }
+}
 
-    f.read_to_string(&mut buffer).expect(&format!("read file {:?} failed", path));
-    let json: Value =
-        serde_json::from_str(&buffer).expect(&format!("parse json failed in file {:?}", path));
+fn parse_file(path: &Path) -> Result<Platform, GenError> {
+    let io_error = |e| {
+        GenError::Io {
+            path: path.to_owned(),
+            error: e,
+        }
+    };
+    let file_stem = file_stem(path)?;
+    let mut f = File::open(path).map_err(&io_error)?;
+    let mut buffer = String::new();
+    f.read_to_string(&mut buffer).map_err(&io_error)?;
+    let json: Value = serde_json::from_str(&buffer).map_err(|e| {
+            GenError::Json {
+                path: path.to_owned(),
+                error: e,
+            }
+        })?;
 
-    let mut p = Platform::from_json(&json);
+    let mut p = Platform::from_json(path, &json)?;
     p.file_stem = file_stem;
-    p
+    Ok(p)
+}
+
+// The name of a platform or instruction set file or directory, e.g. `avx2`.
+fn file_stem(path: &Path) -> Result<String, GenError> {
+    path.file_stem()
+        .and_then(|n| n.to_str())
+        .map(|n| n.to_string())
+        .ok_or_else(|| {
+            GenError::Io {
+                path: path.to_owned(),
+                error: io::Error::new(io::ErrorKind::InvalidInput, "expected a UTF-8 file name"),
+            }
+        })
 }
 
 #[derive(Default, Debug)]
 pub struct Platform {
     pub file_stem: String,
+    path: PathBuf,
     platform: Option<PlatformInfo>,
     intrinsicset: Vec<IntrinsicSet>,
 }
 
 impl Platform {
-    pub fn from_json(json: &Value) -> Self {
-        Platform {
+    pub fn from_json(path: &Path, json: &Value) -> Result<Self, GenError> {
+        Ok(Platform {
             file_stem: String::new(),
-            platform: PlatformInfo::from_json(json),
-            intrinsicset: vec![IntrinsicSet::from_json(json)],
-        }
+            path: path.to_owned(),
+            platform: PlatformInfo::from_json(path, json)?,
+            intrinsicset: vec![IntrinsicSet::from_json(path, json)?],
+        })
     }
 
     pub fn merge(&mut self, mut other: Platform) {
@@ -83,6 +148,28 @@ impl Platform {
         self.intrinsicset.append(&mut other.intrinsicset);
     }
 
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // The file stems of the instruction sets that define intrinsics.
+    pub fn instruction_sets(&self) -> Vec<&str> {
+        self.intrinsicset
+            .iter()
+            .filter(|s| !s.intrinsics.is_empty())
+            .map(|s| &s.file_stem[..])
+            .collect()
+    }
+
+    // Keeps only the instruction sets whose file stem `f` accepts.
+    pub fn retain_sets<F: FnMut(&str) -> bool>(&mut self, mut f: F) {
+        self.intrinsicset.retain(|s| f(&s.file_stem));
+    }
+
+    pub fn platform_info(&self) -> Option<&PlatformInfo> {
+        self.platform.as_ref()
+    }
+
     pub fn platform_prefix(&self) -> String {
         self.platform
             .as_ref()
@@ -90,108 +177,181 @@ impl Platform {
             .unwrap_or_else(String::new)
     }
 
-    fn widths(&self) -> Vec<i32> {
-        self.platform
-            .as_ref()
-            .map(|p| p.width_info.iter().map(|w| w.width).collect())
-            .unwrap_or_else(|| vec![])
+    fn widths(&self,
+              info: &PlatformInfo,
+              s: &IntrinsicSet,
+              i: &IntrinsicData)
+              -> Result<Vec<i32>, GenError> {
+        let mut result = vec![];
+        for (idx, w) in i.width.iter().enumerate() {
+            let error = |reason: String| {
+                GenError::InvalidWidth {
+                    path: s.path.clone(),
+                    pointer: format!("{}/width/{}", i.pointer, idx),
+                    width: w.clone(),
+                    reason: reason,
+                }
+            };
+            let w: i32 = w.parse().map_err(|_| error("expected an integer".to_string()))?;
+            // must be a power of two; `0` marks intrinsics that only take scalars
+            if w < 0 || w & (w - 1) != 0 {
+                return Err(error("must be a power of two".to_string()));
+            }
+            if w != 0 && info.width_info(w).is_none() {
+                return Err(error(format!("no entry in the width_info of platform `{}`",
+                                         info.name)));
+            }
+            result.push(w);
+        }
+        Ok(result)
     }
 
-    pub fn monomorphise(&self) -> Vec<MonomorphicIntrinsic> {
+    pub fn monomorphise(&self) -> Result<Vec<MonomorphicIntrinsic>, Vec<GenError>> {
+        let info = match self.platform {
+            Some(ref info) => info,
+            None => return Err(vec![GenError::MissingPlatformInfo { path: self.path.clone() }]),
+        };
+
         let mut result = vec![];
+        let mut errors = vec![];
+        for s in &self.intrinsicset {
+            for i in &s.intrinsics {
+                match self.monomorphise_intrinsic(info, s, i) {
+                    Ok(mut r) => result.append(&mut r),
+                    Err(e) => errors.push(e),
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(result)
+        } else {
+            Err(errors)
+        }
+    }
 
+    fn monomorphise_intrinsic(&self,
+                              info: &PlatformInfo,
+                              s: &IntrinsicSet,
+                              i: &IntrinsicData)
+                              -> Result<Vec<MonomorphicIntrinsic>, GenError> {
+        // On failure, reports which of ret/args went wrong along with the error.
         fn recur(width: i32,
                  processed: &[Type],
                  untouched: &[TypeSpec])
-                 -> Vec<MonomorphicIntrinsic> {
+                 -> Result<Vec<MonomorphicIntrinsic>, (usize, SpecError)> {
             if untouched.is_empty() {
                 let ret = &processed[0];
                 let args = &processed[1..];
                 let m = MonomorphicIntrinsic::from_types(ret, args);
-                vec![m]
+                Ok(vec![m])
             } else {
                 let mut result = vec![];
                 let raw_arg = &untouched[0];
                 let rest = &untouched[1..];
-                for arg in raw_arg.enumerate(width, processed) {
+                let choices = raw_arg.enumerate(width, processed)
+                    .map_err(|e| (processed.len(), e))?;
+                for arg in choices {
                     let mut extend: Vec<Type> = processed.into();
                     extend.push(arg);
-                    for intr in recur(width, &extend[..], rest) {
+                    for intr in recur(width, &extend[..], rest)? {
                         result.push(intr);
                     }
                 }
-                result
+                Ok(result)
             }
         }
 
-        for s in &self.intrinsicset {
-            for i in &s.intrinsics {
-                let ret = TypeSpec::from_list(&i.ret[..]);
-                let mut args: Vec<_> = i.args.iter().map(|s| TypeSpec::from_str(s)).collect();
-                for w in self.widths() {
-                    assert!(w & (w - 1) == 0);
-                    let p = [];
-                    let mut u = vec![ret.clone()];
-                    u.append(&mut args);
-                    let mut r = recur(w, &p, &u[..]);
-                    for mut m in &mut r {
-                        m.update(w, self, s, i);
-                    }
-                    result.append(&mut r);
-                }
+        let mut result = vec![];
+        let spec_error = |(position, e): (usize, SpecError)| {
+            GenError::InvalidTypeSpec {
+                path: s.path.clone(),
+                pointer: i.spec_pointer(position, e.index),
+                spec: e.spec,
+                offset: e.offset,
+                reason: e.reason,
+                help: e.help,
+            }
+        };
+        let mut u = vec![TypeSpec::from_list(&i.ret[..]).map_err(|e| spec_error((0, e)))?];
+        for (k, arg) in i.args.iter().enumerate() {
+            u.push(TypeSpec::from_str(arg).map_err(|e| spec_error((k + 1, e)))?);
+        }
+        for w in self.widths(info, s, i)? {
+            let p = [];
+            let mut r = recur(w, &p, &u[..]).map_err(&spec_error)?;
+            for m in &mut r {
+                m.update(w, info, s, i)?;
             }
+            result.append(&mut r);
         }
-        result
+        Ok(result)
     }
 
-    pub fn generate(&self) -> String {
-        self.monomorphise()
+    pub fn generate(&self) -> Result<String, Vec<GenError>> {
+        Ok(self.monomorphise()?
             .iter()
             .map(|m| m.to_string())
             .collect::<Vec<String>>()
-            .join("")
+            .join(""))
     }
 }
 
 #[derive(Default, Debug, Clone)]
 pub struct PlatformInfo {
+    path: PathBuf,
     name: String,
     number_info: Vec<NumberInfo>,
     width_info: Vec<WidthInfo>,
 }
 
 impl PlatformInfo {
-    pub fn from_json(json: &Value) -> Option<Self> {
-        let p = json.get("platform");
-        let n = json.get("number_info");
-        let w = json.get("width_info");
-        p.map(|p| PlatformInfo {
-            name: p.to_string(),
-            number_info: n.map(NumberInfo::from_json).unwrap_or_else(Vec::new),
-            width_info: w.map(WidthInfo::from_json).unwrap_or_else(Vec::new),
-        })
+    pub fn from_json(path: &Path, json: &Value) -> Result<Option<Self>, GenError> {
+        if json.get("platform").is_none() {
+            return Ok(None);
+        }
+        Ok(Some(PlatformInfo {
+            path: path.to_owned(),
+            name: read_string(path, json, "", "platform", true)?,
+            number_info: NumberInfo::from_json(path, json.get("number_info"))?,
+            width_info: WidthInfo::from_json(path, json.get("width_info"))?,
+        }))
+    }
+
+    // The file the platform information came from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn number_info(&self, ty: &str) -> Option<&NumberInfo> {
+        self.number_info.iter().find(|n| n.ty == ty)
+    }
+
+    pub fn width_info(&self, width: i32) -> Option<&WidthInfo> {
+        self.width_info.iter().find(|w| w.width == width)
     }
 }
 
 #[derive(Default, Debug, Clone)]
 pub struct NumberInfo {
     ty: String,
-    props: Value,
+    pub props: Value,
 }
 
 impl NumberInfo {
-    pub fn from_json(json: &Value) -> Vec<NumberInfo> {
+    pub fn from_json(path: &Path, json: Option<&Value>) -> Result<Vec<NumberInfo>, GenError> {
         let mut res = Vec::new();
-        if let &Value::Object(ref map) = json {
-            for (k, v) in map {
-                let item = NumberInfo {
-                    ty: k.clone(),
-                    props: v.clone(),
-                };
-                res.push(item);
-            }
+        for (k, v) in read_object(path, json, "/number_info")? {
+            let item = NumberInfo {
+                ty: k.clone(),
+                props: v.clone(),
+            };
+            res.push(item);
         }
-        res
+        Ok(res)
     }
 }
 
@@ -202,117 +362,199 @@ pub struct WidthInfo {
 }
 
 impl WidthInfo {
-    pub fn from_json(json: &Value) -> Vec<WidthInfo> {
+    pub fn from_json(path: &Path, json: Option<&Value>) -> Result<Vec<WidthInfo>, GenError> {
         let mut res = Vec::new();
-        if let &Value::Object(ref map) = json {
-            for (k, v) in map {
-                let item = WidthInfo {
-                    width: k.parse().expect(""),
-                    props: v.clone(),
-                };
-                res.push(item);
-            }
+        for (k, v) in read_object(path, json, "/width_info")? {
+            let width = k.parse().map_err(|_| {
+                    GenError::InvalidWidth {
+                        path: path.to_owned(),
+                        pointer: format!("/width_info/{}", k),
+                        width: k.clone(),
+                        reason: "expected an integer".to_string(),
+                    }
+                })?;
+            let item = WidthInfo {
+                width: width,
+                props: v.clone(),
+            };
+            res.push(item);
         }
-        res
+        Ok(res)
     }
 }
 
 #[derive(Default, Debug, Clone)]
 pub struct IntrinsicSet {
+    path: PathBuf,
+    // the instruction set, e.g. `avx2` for `x86/avx2.json`
+    file_stem: String,
     intrinsic_prefix: String,
     llvm_prefix: String,
     intrinsics: Vec<IntrinsicData>,
 }
 
 impl IntrinsicSet {
-    pub fn from_json(json: &Value) -> IntrinsicSet {
+    pub fn from_json(path: &Path, json: &Value) -> Result<IntrinsicSet, GenError> {
         let mut data = IntrinsicSet::default();
-        data.intrinsic_prefix = json.get("intrinsic_prefix")
-            .map_or(Some(String::new()), |s| match *s {
-                Value::String(ref s) => Some(s.clone()),
-                Value::Null => Some(String::new()),
-                _ => {
-                    println!("cargo:warning=Got {:?}", s);
-                    None
-                }
-            })
-            .expect("expected a string value for field intrinsic_prefix");
-        data.llvm_prefix = json.get("llvm_prefix")
-            .map_or(Some(String::new()), |s| match *s {
-                Value::String(ref s) => Some(s.clone()),
-                Value::Null => Some(String::new()),
-                _ =>  {
-                    println!("cargo:warning=Got {:?}", s);
-                    None
+        data.path = path.to_owned();
+        data.file_stem = path.file_stem()
+            .map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+        data.intrinsic_prefix = read_string(path, json, "", "intrinsic_prefix", false)?;
+        data.llvm_prefix = read_string(path, json, "", "llvm_prefix", false)?;
+
+        match json.get("intrinsics") {
+            Some(&Value::Array(ref arr)) => {
+                for (idx, item) in arr.iter().enumerate() {
+                    let pointer = format!("/intrinsics/{}", idx);
+                    let i = IntrinsicData::from_json(path, &pointer, item)?;
+                    data.intrinsics.push(i);
                 }
-            })
-            .expect("expected a string value for field llvm_prefix");
-
-        let intrisics = json.get("intrinsics");
-        if let Some(&Value::Array(ref arr)) = intrisics {
-            for item in arr {
-                let i = IntrinsicData::from_json(item);
-                data.intrinsics.push(i);
+            }
+            None => {}
+            Some(v) => {
+                return Err(GenError::InvalidField {
+                    path: path.to_owned(),
+                    pointer: "/intrinsics".to_string(),
+                    expected: "an array",
+                    found: v.to_string(),
+                })
             }
         }
-        data
+        Ok(data)
     }
 }
 
 #[derive(Default, Debug, Clone)]
 pub struct IntrinsicData {
+    pointer: String,
     intrinsic: String,
     width: Vec<String>,
     llvm: String,
     ret: Vec<String>,
+    ret_is_list: bool,
     args: Vec<String>,
 }
 
 impl IntrinsicData {
-    pub fn from_json(json: &Value) -> IntrinsicData {
-        IntrinsicData {
-            intrinsic: json.get("intrinsic")
-                .map_or(Some(String::new()), |s| match *s {
-                    Value::String(ref s) => Some(s.clone()),
-                    Value::Null => Some(String::new()),
-                    _ =>  {
-                        println!("cargo:warning=Got {:?}", s);
-                        None
-                    }
-                })
-                .expect("expected a string value for field llvm_prefix"),
-            width: read_array(json.get("width")),
-            llvm: json.get("llvm")
-                .map_or(Some(String::new()), |s| match *s {
-                    Value::String(ref s) => Some(s.clone()),
-                    Value::Null => Some(String::new()),
-                    _ => {
-                        println!("cargo:warning=Got {:?}", s);
-                        None
-                    }
-                })
-                .expect("expected a string value for field llvm"),
-            ret: read_array(json.get("ret")),
-            args: read_array(json.get("args")),
+    pub fn from_json(path: &Path, pointer: &str, json: &Value) -> Result<IntrinsicData, GenError> {
+        Ok(IntrinsicData {
+            pointer: pointer.to_string(),
+            intrinsic: read_string(path, json, pointer, "intrinsic", true)?,
+            width: read_array(path, json, pointer, "width")?,
+            llvm: read_string(path, json, pointer, "llvm", true)?,
+            ret: read_array(path, json, pointer, "ret")?,
+            ret_is_list: json.get("ret").map_or(false, Value::is_array),
+            args: read_array(path, json, pointer, "args")?,
+        })
+    }
+
+    // `position` 0 is the return type, `n` the `n`th argument; `index` picks
+    // an entry of a `ret` list.
+    fn spec_pointer(&self, position: usize, index: usize) -> String {
+        if position == 0 {
+            if self.ret_is_list {
+                format!("{}/ret/{}", self.pointer, index)
+            } else {
+                format!("{}/ret", self.pointer)
+            }
+        } else {
+            format!("{}/args/{}", self.pointer, position - 1)
+        }
+    }
+
+    // The entry of the `ret` list that `ty` was enumerated from at `width`.
+    fn ret_index(&self, width: i32, ty: &Type) -> usize {
+        self.ret
+            .iter()
+            .position(|r| {
+                TypeSpec::from_str(r)
+                    .and_then(|spec| spec.enumerate(width, &[]))
+                    .map_or(false, |choices| choices.contains(ty))
+            })
+            .unwrap_or(0)
+    }
+}
+
+fn read_string(path: &Path,
+               json: &Value,
+               pointer: &str,
+               field: &str,
+               required: bool)
+               -> Result<String, GenError> {
+    match json.get(field) {
+        Some(&Value::String(ref s)) => Ok(s.clone()),
+        None | Some(&Value::Null) if !required => Ok(String::new()),
+        v => {
+            Err(GenError::InvalidField {
+                path: path.to_owned(),
+                pointer: format!("{}/{}", pointer, field),
+                expected: "a string",
+                found: v.map_or_else(|| "nothing".to_string(), Value::to_string),
+            })
         }
     }
 }
 
-fn read_array(json: Option<&Value>) -> Vec<String> {
-    match json {
+fn read_array(path: &Path,
+              json: &Value,
+              pointer: &str,
+              field: &str)
+              -> Result<Vec<String>, GenError> {
+    let error = |v: &Value| {
+        GenError::InvalidField {
+            path: path.to_owned(),
+            pointer: format!("{}/{}", pointer, field),
+            expected: "a string or an array of strings",
+            found: v.to_string(),
+        }
+    };
+    match json.get(field) {
         Some(&Value::Array(ref arr)) => {
-            arr.iter()
-                .map(|v| v.to_string().trim_matches('"').to_string())
-                .collect()
+            let mut result = vec![];
+            for v in arr {
+                match *v {
+                    Value::String(ref s) => result.push(s.clone()),
+                    Value::Number(ref n) => result.push(n.to_string()),
+                    _ => return Err(error(v)),
+                }
+            }
+            Ok(result)
+        }
+        Some(&Value::String(ref s)) => Ok(vec![s.clone()]),
+        Some(v) => Err(error(v)),
+        None => {
+            Err(GenError::InvalidField {
+                path: path.to_owned(),
+                pointer: format!("{}/{}", pointer, field),
+                expected: "a string or an array of strings",
+                found: "nothing".to_string(),
+            })
+        }
+    }
+}
+
+fn read_object<'a>(path: &Path,
+                   json: Option<&'a Value>,
+                   pointer: &str)
+                   -> Result<Vec<(&'a String, &'a Value)>, GenError> {
+    match json {
+        Some(&Value::Object(ref map)) => Ok(map.iter().collect()),
+        None => Ok(vec![]),
+        Some(v) => {
+            Err(GenError::InvalidField {
+                path: path.to_owned(),
+                pointer: pointer.to_string(),
+                expected: "an object",
+                found: v.to_string(),
+            })
         }
-        Some(&Value::String(ref s)) => vec![s.to_string().trim_matches('"').to_string()],
-        _ => Vec::new(),
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct MonomorphicIntrinsic {
     intrinsic_set_name: String,
+    instruction_set: String,
     platform_prefix: String,
     len: usize,
     llvm_name: String,
@@ -324,6 +566,7 @@ impl MonomorphicIntrinsic {
     fn from_types(ret: &Type, args: &[Type]) -> Self {
         MonomorphicIntrinsic {
             intrinsic_set_name: Default::default(),
+            instruction_set: Default::default(),
             platform_prefix: Default::default(),
             len: 0,
             llvm_name: Default::default(),
@@ -332,29 +575,123 @@ impl MonomorphicIntrinsic {
         }
     }
 
-    fn update(&mut self, w: i32, p: &Platform, s: &IntrinsicSet, i: &IntrinsicData) {
-        self.intrinsic_set_name = format!("{}{}", s.intrinsic_prefix, i.intrinsic);
-        self.platform_prefix = p.platform_prefix();
+    fn update(&mut self,
+              w: i32,
+              info: &PlatformInfo,
+              s: &IntrinsicSet,
+              i: &IntrinsicData)
+              -> Result<(), GenError> {
+        // `position` as in `IntrinsicData::spec_pointer`
+        let type_info = |position: usize, t: &Type| {
+            typespec::type_info(t, info).map_err(|reason| {
+                let (index, spec) = if position == 0 {
+                    let index = i.ret_index(w, t);
+                    (index, i.ret[index].clone())
+                } else {
+                    (0, i.args[position - 1].clone())
+                };
+                GenError::InvalidTypeSpec {
+                    path: s.path.clone(),
+                    pointer: i.spec_pointer(position, index),
+                    spec: spec,
+                    offset: 0,
+                    reason: format!("{} (needed for {})", reason, t.rust_name()),
+                    help: None,
+                }
+            })
+        };
+        let ret = type_info(0, &self.ret)?;
+        let mut args = vec![];
+        for (k, a) in self.args.iter().enumerate() {
+            args.push(type_info(k + 1, a)?);
+        }
+        let mut positional = vec![ret.as_ref()];
+        positional.extend(args.iter().map(|a| a.as_ref()));
+        let width = if w == 64 { "" } else { "q" };
+        let named = [("width", width)];
+        let expand = |field: &str, template: &str, named: &[(&str, &str)]| {
+            template::format(template, &positional, named).map_err(|reason| {
+                GenError::InvalidTemplate {
+                    path: s.path.clone(),
+                    pointer: format!("{}/{}", i.pointer, field),
+                    template: template.to_string(),
+                    reason: reason,
+                }
+            })
+        };
+
+        self.intrinsic_set_name = format!("{}{}",
+                                          s.intrinsic_prefix,
+                                          expand("intrinsic", &i.intrinsic, &named)?);
+        self.instruction_set = s.file_stem.clone();
+        self.platform_prefix = info.name.clone();
         self.len = self.args.len();
         self.llvm_name = if i.llvm.starts_with('!') {
-            // FIXME(laumann): What does the ! mean?
-            i.llvm[1..].into() // TODO: format
+            // A leading `!` means the name is used as-is, without the set's `llvm_prefix`.
+            expand("llvm", &i.llvm[1..], &[])?
         } else {
-            s.llvm_prefix.clone() + &i.llvm[1..] // TODO: format
+            s.llvm_prefix.clone() + &expand("llvm", &i.llvm, &[])?
         };
+        Ok(())
+    }
+
+    // The name without the platform prefix, e.g. `_mm256_abs_epi8`.
+    pub fn name(&self) -> &str {
+        &self.intrinsic_set_name
+    }
+
+    pub fn platform_prefix(&self) -> &str {
+        &self.platform_prefix
+    }
+
+    // The file stem of the instruction set it was defined in, e.g. `avx2`.
+    // Sets only decide which intrinsics are generated; neither the tables nor
+    // `Intrinsic` record them.
+    pub fn instruction_set(&self) -> &str {
+        &self.instruction_set
     }
 
-    fn compiler_args(&self) -> String {
+    pub fn llvm_name(&self) -> &str {
+        &self.llvm_name
+    }
+
+    pub fn args(&self) -> &[Type] {
+        &self.args
+    }
+
+    pub fn ret(&self) -> &Type {
+        &self.ret
+    }
+
+    pub fn compiler_args(&self) -> String {
         self.args
             .iter()
             .map(|a| a.compiler_ctor_ref())
             .collect::<Vec<String>>()
-            .join(",")
+            .join(", ")
     }
 
-    fn compiler_ret(&self) -> String {
+    pub fn compiler_ret(&self) -> String {
         self.ret.compiler_ctor_ref()
     }
+
+    // The Rust signature, e.g. `(x: i8x32, y: i8x32) -> i8x32`. Past the ten
+    // names `generator.py` has, arguments are `arg10`, `arg11` and so on.
+    pub fn intrinsic_signature(&self) -> String {
+        let names = "xyzwabcdef";
+        format!("({}) -> {}",
+                self.args
+                    .iter()
+                    .enumerate()
+                    .map(|(i, a)| {
+                        let name = names.get(i..i + 1)
+                            .map_or_else(|| format!("arg{}", i), String::from);
+                        format!("{}: {}", name, a.rust_name())
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                self.ret.rust_name())
+    }
 }
 
 impl Display for MonomorphicIntrinsic {
@@ -0,0 +1,136 @@
+// A perfect hash table built at generation time, using the "hash, displace
+// and compress" scheme of the `phf` crate: keys are split into buckets by
+// one part of their hash, then each bucket, largest first, gets a
+// displacement `(d1, d2)` that moves all of its keys into free slots.
+//
+// The generated code looks keys up with `LOOKUP_FNS`, the source of the
+// same functions `build` places the keys with.
+
+use error::GenError;
+use std::path::Path;
+
+include!("phf_lookup.rs");
+
+pub static LOOKUP_FNS: &'static str = include_str!("phf_lookup.rs");
+
+// Average number of keys per bucket.
+const LAMBDA: usize = 5;
+
+// The generated tables index intrinsics with a `u16`.
+pub const MAX_KEYS: usize = 1 << 16;
+
+pub struct Table {
+    pub seed: u64,
+    pub disps: Vec<(u32, u32)>,
+    // `order[slot]` is the index of the key stored in `slot`
+    pub order: Vec<usize>,
+}
+
+// The lookup tables of a platform's intrinsics, given as `(full name, LLVM
+// name)` pairs.
+pub struct Tables {
+    // The indices of the intrinsics kept, sorted by name. Like a `match`,
+    // the first definition of a name wins.
+    pub entries: Vec<usize>,
+    // Positions in `entries`, sorted by LLVM name.
+    pub by_llvm_name: Vec<u16>,
+    // The perfect hash of the names in `entries`, unless there are none.
+    pub hash: Option<Table>,
+}
+
+pub fn tables(path: &Path, intrinsics: &[(&str, &str)]) -> Result<Tables, GenError> {
+    let mut entries: Vec<usize> = (0..intrinsics.len()).collect();
+    // stable, so that the first definition of a name comes first
+    entries.sort_by(|&a, &b| intrinsics[a].0.cmp(intrinsics[b].0));
+    entries.dedup_by(|a, b| intrinsics[*a].0 == intrinsics[*b].0);
+    if entries.len() > MAX_KEYS {
+        return Err(GenError::TooManyIntrinsics {
+            path: path.to_owned(),
+            count: entries.len(),
+        });
+    }
+    let mut by_llvm_name: Vec<u16> = (0..entries.len()).map(|i| i as u16).collect();
+    by_llvm_name.sort_by_key(|&i| (intrinsics[entries[i as usize]].1, i));
+    let hash = if entries.is_empty() {
+        None
+    } else {
+        let keys: Vec<&str> = entries.iter().map(|&i| intrinsics[i].0).collect();
+        Some(build(&keys))
+    };
+    Ok(Tables {
+        entries: entries,
+        by_llvm_name: by_llvm_name,
+        hash: hash,
+    })
+}
+
+pub fn hash(key: &str, seed: u64) -> u64 {
+    phf_hash(key, seed)
+}
+
+// The slot of `key` in a table of `len` entries.
+pub fn index(key: &str, seed: u64, disps: &[(u32, u32)], len: usize) -> usize {
+    phf_index(key, seed, disps, len)
+}
+
+// `keys` must be unique and non-empty.
+pub fn build(keys: &[&str]) -> Table {
+    assert!(!keys.is_empty());
+    let mut seed = 0;
+    loop {
+        if let Some(table) = try_build(keys, seed) {
+            return table;
+        }
+        seed += 1;
+    }
+}
+
+fn try_build(keys: &[&str], seed: u64) -> Option<Table> {
+    let len = keys.len();
+    let hashes: Vec<(u32, u32, u32)> =
+        keys.iter().map(|k| phf_split(phf_hash(k, seed))).collect();
+
+    let mut buckets = vec![vec![]; (len + LAMBDA - 1) / LAMBDA];
+    for (i, h) in hashes.iter().enumerate() {
+        let bucket = h.0 as usize % buckets.len();
+        buckets[bucket].push(i);
+    }
+    let mut by_size: Vec<usize> = (0..buckets.len()).collect();
+    by_size.sort_by(|&a, &b| buckets[b].len().cmp(&buckets[a].len()));
+
+    let mut slots: Vec<Option<usize>> = vec![None; len];
+    let mut disps = vec![(0, 0); buckets.len()];
+    // `tried[slot] == generation` marks slots taken by the current attempt
+    let mut tried = vec![0u64; len];
+    let mut generation = 0;
+    let mut placed = vec![];
+    'buckets: for &b in &by_size {
+        for d1 in 0..len as u32 {
+            'disps: for d2 in 0..len as u32 {
+                generation += 1;
+                placed.clear();
+                for &k in &buckets[b] {
+                    let (_, f1, f2) = hashes[k];
+                    let slot = phf_displace(f1, f2, d1, d2) as usize % len;
+                    if slots[slot].is_some() || tried[slot] == generation {
+                        continue 'disps;
+                    }
+                    tried[slot] = generation;
+                    placed.push((slot, k));
+                }
+                for &(slot, k) in &placed {
+                    slots[slot] = Some(k);
+                }
+                disps[b] = (d1, d2);
+                continue 'buckets;
+            }
+        }
+        return None;
+    }
+
+    Some(Table {
+        seed: seed,
+        disps: disps,
+        order: slots.into_iter().map(|k| k.unwrap()).collect(),
+    })
+}
@@ -0,0 +1,28 @@
+// The perfect hash lookup; see `intrinsic_gen::phf`.
+fn phf_hash(key: &str, seed: u64) -> u64 {
+    let mut h = 0xcbf29ce484222325 ^ seed;
+    for b in key.bytes() {
+        h ^= b as u64;
+        h = h.wrapping_mul(0x100000001b3);
+    }
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xff51afd7ed558ccd);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xc4ceb9fe1a85ec53);
+    h ^ (h >> 33)
+}
+
+// The bucket of a hash and the two values its displacement combines.
+fn phf_split(h: u64) -> (u32, u32, u32) {
+    ((h >> 32) as u32, h as u32, (h.wrapping_mul(0x9e3779b97f4a7c15) >> 32) as u32)
+}
+
+fn phf_displace(f1: u32, f2: u32, d1: u32, d2: u32) -> u32 {
+    d2.wrapping_add(f1.wrapping_mul(d1)).wrapping_add(f2)
+}
+
+fn phf_index(key: &str, seed: u64, disps: &[(u32, u32)], len: usize) -> usize {
+    let (bucket, f1, f2) = phf_split(phf_hash(key, seed));
+    let (d1, d2) = disps[bucket as usize % disps.len()];
+    phf_displace(f1, f2, d1, d2) as usize % len
+}
@@ -0,0 +1,117 @@
+use typespec::PlatformTypeInfo;
+
+// A small subset of Python's `str.format`, enough for the `intrinsic` and
+// `llvm` patterns in the JSON files: `{0.data_type}`, `{0[0].width}`,
+// `{0.llvm_name}`, `{width}`, plus `{{` and `}}` escapes.
+
+enum Value<'a> {
+    Info(&'a PlatformTypeInfo),
+    Str(&'a str),
+}
+
+pub fn format(template: &str,
+              positional: &[Option<&PlatformTypeInfo>],
+              named: &[(&str, &str)])
+              -> Result<String, String> {
+    let mut result = String::with_capacity(template.len());
+    let mut chars = template.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' => {
+                if let Some(&(_, '{')) = chars.peek() {
+                    chars.next();
+                    result.push('{');
+                    continue;
+                }
+                let start = i + 1;
+                let end = loop {
+                    match chars.next() {
+                        Some((j, '}')) => break j,
+                        Some((_, '{')) | None => {
+                            return Err("unterminated replacement field".to_string())
+                        }
+                        Some(_) => {}
+                    }
+                };
+                let field = &template[start..end];
+                result.push_str(resolve(field, positional, named)?);
+            }
+            '}' => {
+                if let Some(&(_, '}')) = chars.peek() {
+                    chars.next();
+                    result.push('}');
+                } else {
+                    return Err("single `}` encountered".to_string());
+                }
+            }
+            c => result.push(c),
+        }
+    }
+    Ok(result)
+}
+
+fn resolve<'a>(field: &str,
+               positional: &[Option<&'a PlatformTypeInfo>],
+               named: &[(&str, &'a str)])
+               -> Result<&'a str, String> {
+    let arg_end = field.find(|c| c == '.' || c == '[').unwrap_or(field.len());
+    let (arg, mut rest) = field.split_at(arg_end);
+
+    let mut value = if let Ok(idx) = arg.parse::<usize>() {
+        match positional.get(idx) {
+            Some(&Some(info)) => Value::Info(info),
+            Some(&None) => {
+                return Err(format!("`{{{}}}` refers to a type without type information", field))
+            }
+            None => {
+                return Err(format!("`{{{}}}` refers to argument {}, but only {} are known",
+                                   field,
+                                   idx,
+                                   positional.len()))
+            }
+        }
+    } else {
+        match named.iter().find(|&&(k, _)| k == arg) {
+            Some(&(_, v)) => Value::Str(v),
+            None => return Err(format!("unknown name `{}` in `{{{}}}`", arg, field)),
+        }
+    };
+
+    while !rest.is_empty() {
+        let info = match value {
+            Value::Info(info) => info,
+            Value::Str(_) => return Err(format!("cannot access `{}` of a string in `{{{}}}`",
+                                                rest,
+                                                field)),
+        };
+        if rest.starts_with('.') {
+            let end = rest[1..].find(|c| c == '.' || c == '[').map_or(rest.len(), |e| e + 1);
+            let attr = &rest[1..end];
+            value = match info.get(attr) {
+                Some(s) => Value::Str(s),
+                None => return Err(format!("unknown property `{}` in `{{{}}}`", attr, field)),
+            };
+            rest = &rest[end..];
+        } else {
+            let end = rest.find(']')
+                .ok_or_else(|| format!("missing `]` in `{{{}}}`", field))?;
+            let idx: usize = rest[1..end]
+                .parse()
+                .map_err(|_| format!("expected an index in `{{{}}}`", field))?;
+            value = match info.elem(idx) {
+                Some(elem) => Value::Info(elem),
+                None => return Err(format!("index {} out of range in `{{{}}}`", idx, field)),
+            };
+            rest = &rest[end + 1..];
+        }
+    }
+
+    match value {
+        Value::Str(s) => Ok(s),
+        Value::Info(info) => {
+            Err(format!("`{{{}}}` names the type {}, not one of its properties",
+                        field,
+                        info.llvm_name()))
+        }
+    }
+}
@@ -0,0 +1,61 @@
+// Checks how tuple and array specs enumerate: every combination for `(...)`,
+// a single shared element type for `[T; N]`, and `.N` projections.
+
+extern crate intrinsic_gen;
+
+use intrinsic_gen::typespec::{Type, TypeSpec};
+
+fn enumerate(spec: &str, previous: &[Type]) -> Vec<String> {
+    TypeSpec::from_str(spec)
+        .unwrap()
+        .enumerate(128, previous)
+        .unwrap()
+        .iter()
+        .map(|t| t.rust_name())
+        .collect()
+}
+
+fn flatten(ty: &Type) -> bool {
+    match *ty {
+        Type::Aggregate { flatten, .. } => flatten,
+        ref ty => panic!("expected an aggregate, found {:?}", ty),
+    }
+}
+
+#[test]
+fn tuples() {
+    assert_eq!(enumerate("(I32,S8)", &[]), vec!["(i32, i8)", "(u32, i8)"]);
+    assert_eq!(enumerate("(I(8-16),F32)", &[]),
+               vec!["(i8, f32)", "(u8, f32)", "(i16, f32)", "(u16, f32)"]);
+    assert_eq!(enumerate("(i8,u16)", &[]), vec!["(i8x16, u16x8)", "(u8x16, u16x8)"]);
+    assert_eq!(enumerate("(i8,i16)", &[]).len(), 4);
+}
+
+#[test]
+fn arrays() {
+    assert_eq!(enumerate("[i(8-16);2]", &[]),
+               vec!["(i8x16, i8x16)", "(u8x16, u8x16)", "(i16x8, i16x8)", "(u16x8, u16x8)"]);
+    assert_eq!(enumerate("[F32;3]", &[]), vec!["(f32, f32, f32)"]);
+}
+
+#[test]
+fn flattening() {
+    let types = TypeSpec::from_str("(f32,f64)f").unwrap().enumerate(128, &[]).unwrap();
+    assert!(flatten(&types[0]));
+    let types = TypeSpec::from_str("[s32;2]f").unwrap().enumerate(128, &[]).unwrap();
+    assert!(flatten(&types[0]));
+    let types = TypeSpec::from_str("[s32;2]").unwrap().enumerate(128, &[]).unwrap();
+    assert!(!flatten(&types[0]));
+}
+
+#[test]
+fn projections() {
+    let previous = TypeSpec::from_str("(S8,f32)").unwrap().enumerate(64, &[]).unwrap();
+    assert_eq!(enumerate("0.0", &previous), vec!["i8"]);
+    assert_eq!(enumerate("0.1", &previous), vec!["f32x2"]);
+    assert_eq!(enumerate("0.1S", &previous), vec!["f32"]);
+    match TypeSpec::from_str("0.2").unwrap().enumerate(64, &previous) {
+        Err(e) => assert_eq!(e.reason, "element 2 requested, but the aggregate only has 2"),
+        Ok(types) => panic!("expected an error, found {:?}", types),
+    }
+}
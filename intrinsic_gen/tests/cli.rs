@@ -0,0 +1,61 @@
+// Checks the `intrinsic-gen` command line against `generator.py`'s
+// behaviour.
+
+extern crate intrinsic_gen;
+
+use intrinsic_gen::parse_files;
+use std::env;
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+
+fn input(path: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/input").join(path)
+}
+
+#[test]
+fn info_wins() {
+    // a set file with platform information of its own, named per process so
+    // that concurrent runs don't share it
+    let set = env::temp_dir().join(format!("intrinsic_gen-cli-set-{}.json", process::id()));
+    File::create(&set)
+        .unwrap()
+        .write_all(br#"{
+            "platform": "other_",
+            "number_info": { "signed": {} },
+            "width_info": {},
+            "intrinsic_prefix": "_",
+            "llvm_prefix": "llvm.other.",
+            "intrinsics": [
+                { "intrinsic": "a", "width": ["0"], "llvm": "a", "ret": "S32", "args": [] }
+            ]
+        }"#)
+        .unwrap();
+    let platform = parse_files(Some(&input("nvptx/info.json")), &[set.clone()]);
+    fs::remove_file(&set).unwrap();
+    assert_eq!(platform.unwrap().platform_prefix(), "nvptx");
+}
+
+#[test]
+fn broken_pipe() {
+    let mut bin = env::current_exe().unwrap();
+    bin.pop();
+    if bin.ends_with("deps") {
+        bin.pop();
+    }
+    let mut child = Command::new(bin.join("intrinsic-gen"))
+        .args(&["--format", "phf-table", "-i"])
+        .arg(input("x86/info.json"))
+        .arg(input("x86/avx2.json"))
+        .arg(input("x86/sse2.json"))
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // like `| head -0`, stop reading before the output fits in the pipe
+    drop(child.stdout.take());
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.stderr.is_empty());
+}
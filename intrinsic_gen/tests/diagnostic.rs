@@ -0,0 +1,97 @@
+// Checks how `diagnostic` resolves JSON pointers to byte spans, and the
+// line and column it renders for them.
+
+extern crate intrinsic_gen;
+
+use intrinsic_gen::GenError;
+use intrinsic_gen::diagnostic::{locate, render};
+use std::env;
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::path::PathBuf;
+use std::process;
+
+static SOURCE: &'static str = r#"{
+    "name": "a \"quoted\" ]}, \\",
+    "a/b": { "c~d": [1, 2] },
+    "intrinsics": [
+        { "args": ["0", "1"] },
+        { "é": "ü", "args": [[], {"x": [true, null]}, "0q"] }
+    ]
+}
+"#;
+
+fn at(pointer: &str) -> Option<&'static str> {
+    locate(SOURCE, pointer).map(|(start, end)| &SOURCE[start..end])
+}
+
+fn write_source() -> PathBuf {
+    let path = env::temp_dir().join(format!("intrinsic_gen-diagnostic-{}.json", process::id()));
+    File::create(&path).unwrap().write_all(SOURCE.as_bytes()).unwrap();
+    path
+}
+
+#[test]
+fn pointers() {
+    assert_eq!(at(""), Some(SOURCE.trim_end()));
+    assert_eq!(at("/name"), Some(r#""a \"quoted\" ]}, \\""#));
+    assert_eq!(at("/a~1b"), Some(r#"{ "c~d": [1, 2] }"#));
+    assert_eq!(at("/a~1b/c~0d/1"), Some("2"));
+    assert_eq!(at("/intrinsics/0/args"), Some(r#"["0", "1"]"#));
+    assert_eq!(at("/intrinsics/1/args/1/x/1"), Some("null"));
+    // the last element of each array
+    assert_eq!(at("/intrinsics/1/args/2"), Some(r#""0q""#));
+    assert_eq!(at("/intrinsics/1"),
+               Some(r#"{ "é": "ü", "args": [[], {"x": [true, null]}, "0q"] }"#));
+
+    assert_eq!(at("/intrinsics/2"), None);
+    assert_eq!(at("/intrinsics/1/args/3"), None);
+    assert_eq!(at("/intrinsics/x"), None);
+    assert_eq!(at("/missing"), None);
+    assert_eq!(at("/name/0"), None);
+    // an unterminated string has no end
+    assert_eq!(locate(r#"[1, "2]"#, "/1"), None);
+}
+
+#[test]
+fn rendering() {
+    let path = write_source();
+    let spec_error = GenError::InvalidTypeSpec {
+        path: path.clone(),
+        pointer: "/intrinsics/1/args/2".to_string(),
+        spec: "0q".to_string(),
+        offset: 1,
+        reason: "unknown modifier `q`".to_string(),
+        help: Some("did you mean `d`? it doubles the vector length".to_string()),
+    };
+    // columns count characters, not bytes
+    assert_eq!(render(&spec_error),
+               format!("error: invalid type spec `0q`: unknown modifier `q`\n \
+                        --> {}:6:57\n  |\n6 |         {}\n  |{}^ unknown modifier `q`\n  \
+                        |\n  = help: did you mean `d`? it doubles the vector length",
+                       path.display(),
+                       r#"{ "é": "ü", "args": [[], {"x": [true, null]}, "0q"] }"#,
+                       " ".repeat(57)));
+
+    let field_error = GenError::InvalidField {
+        path: path.clone(),
+        pointer: "/a~1b/c~0d".to_string(),
+        expected: "a string",
+        found: "[1,2]".to_string(),
+    };
+    assert_eq!(render(&field_error),
+               format!("error: expected a string, found `[1,2]`\n --> {}:3:21\n  |\n3 |     {}\n  \
+                        |                     ^^^^^^",
+                       path.display(),
+                       r#""a/b": { "c~d": [1, 2] },"#));
+
+    // without a value to point at, the one-line form
+    let missing = GenError::InvalidField {
+        path: path.clone(),
+        pointer: "/intrinsics/7".to_string(),
+        expected: "a string",
+        found: "1".to_string(),
+    };
+    assert_eq!(render(&missing), format!("error: {}", missing));
+    fs::remove_file(&path).unwrap();
+}
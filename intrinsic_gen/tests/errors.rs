@@ -0,0 +1,158 @@
+// Checks that malformed platforms are reported as `GenError`s pointing at
+// the offending value, rather than panicking.
+
+extern crate intrinsic_gen;
+#[macro_use]
+extern crate serde_json;
+
+use intrinsic_gen::{parse, render_platforms, GenError, Platform};
+use intrinsic_gen::typespec::TypeSpec;
+use serde_json::Value;
+use std::path::Path;
+
+fn platform(intrinsics: Value) -> Platform {
+    named_platform("toy.json", "toy_", intrinsics)
+}
+
+fn named_platform(path: &str, prefix: &str, intrinsics: Value) -> Platform {
+    let json = json!({
+        "platform": prefix,
+        "number_info": { "signed": {}, "float": {} },
+        "width_info": { "128": {} },
+        "intrinsics": intrinsics,
+    });
+    let mut p = Platform::from_json(Path::new(path), &json).unwrap();
+    p.file_stem = path.trim_end_matches(".json").to_string();
+    p
+}
+
+fn type_spec_error(e: &GenError) -> (&str, &str, &str) {
+    match *e {
+        GenError::InvalidTypeSpec { ref pointer, ref spec, ref reason, .. } => {
+            (pointer, spec, reason)
+        }
+        ref e => panic!("expected an invalid type spec, found {:?}", e),
+    }
+}
+
+#[test]
+fn missing_width_info() {
+    let p = platform(json!([
+        { "intrinsic": "a", "width": [128], "llvm": "a", "ret": "f32", "args": ["0x256"] },
+        { "intrinsic": "b", "width": [128], "llvm": "b", "ret": ["s32", "f32x256"], "args": [] },
+    ]));
+    let errors = p.monomorphise().err().unwrap();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(type_spec_error(&errors[0]),
+               ("/intrinsics/0/args/0",
+                "0x256",
+                "no width_info for vectors of width 256 (needed for f32x8)"));
+    assert_eq!(type_spec_error(&errors[1]),
+               ("/intrinsics/1/ret/1",
+                "f32x256",
+                "no width_info for vectors of width 256 (needed for f32x8)"));
+}
+
+#[test]
+fn no_file_name() {
+    match parse(Path::new("..")) {
+        Err(ref errors) if errors.len() == 1 => {
+            match errors[0] {
+                GenError::Io { ref path, .. } => assert_eq!(path, Path::new("..")),
+                ref e => panic!("expected an I/O error, found {:?}", e),
+            }
+        }
+        result => panic!("expected one error, found {:?}", result),
+    }
+}
+
+#[test]
+fn overlapping_platforms() {
+    let platforms = [named_platform("toy.json", "toy_", json!([])),
+                     named_platform("other.json", "other_", json!([])),
+                     named_platform("toy_x.json", "toy_x", json!([]))];
+    let errors = render_platforms(&platforms).err().unwrap();
+    assert_eq!(errors.len(), 1);
+    match errors[0] {
+        GenError::OverlappingPlatforms { ref path,
+                                         ref pointer,
+                                         ref prefix,
+                                         ref other_path,
+                                         ref other_prefix } => {
+            assert_eq!(path, Path::new("toy_x.json"));
+            assert_eq!(pointer, "/platform");
+            assert_eq!(prefix, "toy_x");
+            assert_eq!(other_path, Path::new("toy.json"));
+            assert_eq!(other_prefix, "toy_");
+        }
+        ref e => panic!("expected overlapping platforms, found {:?}", e),
+    }
+    assert_eq!(errors[0].to_string(),
+               "toy_x.json: /platform: platform prefix `toy_x` overlaps with `toy_` from toy.json");
+
+    assert!(render_platforms(&platforms[..2]).is_ok());
+}
+
+#[test]
+fn invalid_widths() {
+    let width_error = |width: Value| {
+        let p = platform(json!([
+            { "intrinsic": "a", "width": width, "llvm": "a", "ret": "f32", "args": [] },
+        ]));
+        let errors = p.monomorphise().err().unwrap();
+        assert_eq!(errors.len(), 1);
+        match errors[0] {
+            GenError::InvalidWidth { ref pointer, ref width, ref reason, .. } => {
+                (pointer.clone(), width.clone(), reason.clone())
+            }
+            ref e => panic!("expected an invalid width, found {:?}", e),
+        }
+    };
+    assert_eq!(width_error(json!([128, "wide"])),
+               ("/intrinsics/0/width/1".to_string(),
+                "wide".to_string(),
+                "expected an integer".to_string()));
+    assert_eq!(width_error(json!([96])).2, "must be a power of two");
+    assert_eq!(width_error(json!([256])).2,
+               "no entry in the width_info of platform `toy_`");
+}
+
+#[test]
+fn vectors_that_dont_fit_the_width() {
+    let p = platform(json!([
+        { "intrinsic": "a", "width": ["0"], "llvm": "a", "ret": "S32", "args": ["f32"] },
+        { "intrinsic": "b", "width": [128], "llvm": "b", "ret": "i(64-256)", "args": [] },
+    ]));
+    let errors = p.monomorphise().err().unwrap();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(type_spec_error(&errors[0]),
+               ("/intrinsics/0/args/0",
+                "f32",
+                "vectors need a width, but the intrinsic only has width 0"));
+    assert_eq!(type_spec_error(&errors[1]),
+               ("/intrinsics/1/ret",
+                "i(64-256)",
+                "a width of 128 doesn't hold a whole number of 256-bit elements"));
+}
+
+#[test]
+fn zero_bitwidth() {
+    let p = platform(json!([
+        { "intrinsic": "a", "width": [128], "llvm": "a", "ret": "S8nnnnv", "args": [] },
+    ]));
+    let errors = p.monomorphise().err().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(type_spec_error(&errors[0]),
+               ("/intrinsics/0/ret", "S8nnnnv", "`n` cannot be applied to i1"));
+}
+
+#[test]
+fn huge_bitwidth_range() {
+    // stops doubling before the bitwidth overflows
+    let types = TypeSpec::from_str("I(8-2147483647)").unwrap().enumerate(128, &[]).unwrap();
+    assert_eq!(types.len(), 2 * 28);
+    assert_eq!(types.last().unwrap().rust_name(), "u1073741824");
+
+    let e = TypeSpec::from_str("i(8-2147483647)").unwrap().enumerate(128, &[]).unwrap_err();
+    assert_eq!(e.reason, "a width of 128 doesn't hold a whole number of 256-bit elements");
+}
@@ -0,0 +1,39 @@
+// Checks the `extern-block` output format: one declaration per monomorphised
+// intrinsic, with its full name and Rust signature.
+
+extern crate intrinsic_gen;
+#[macro_use]
+extern crate serde_json;
+
+use intrinsic_gen::{parse, render_format, ExternBlock, Platform};
+use std::path::Path;
+
+#[test]
+fn x86() {
+    let p = parse(&Path::new(env!("CARGO_MANIFEST_DIR")).join("../tests/input/x86")).unwrap();
+    let output = render_format(&p, &ExternBlock).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[0], r#"extern "platform-intrinsic" {"#);
+    assert_eq!(lines[lines.len() - 1], "}");
+    assert_eq!(lines.len(), p.monomorphise().unwrap().len() + 2);
+    assert!(lines.contains(&"    fn x86_mm256_abs_epi8(x: i8x32) -> i8x32;"));
+    assert!(lines.contains(&"    fn x86_mm_mask_i32gather_epi32(x: i32x4, y: *const i32, z: \
+                             i32x4, w: i32x4, a: i32) -> i32x4;"));
+}
+
+#[test]
+fn many_arguments() {
+    let json = json!({
+        "platform": "toy_",
+        "number_info": { "signed": {} },
+        "width_info": {},
+        "intrinsics": [
+            { "intrinsic": "many", "width": ["0"], "llvm": "many", "ret": "V",
+              "args": ["S8", "S8", "S8", "S8", "S8", "S8", "S8", "S8", "S8", "S8", "S16", "S32"] },
+        ],
+    });
+    let p = Platform::from_json(Path::new("toy.json"), &json).unwrap();
+    assert_eq!(render_format(&p, &ExternBlock).unwrap(),
+               "extern \"platform-intrinsic\" {\n    fn toy_many(x: i8, y: i8, z: i8, w: i8, a: i8, \
+                b: i8, c: i8, d: i8, e: i8, f: i8, arg10: i16, arg11: i32) -> ();\n}\n");
+}
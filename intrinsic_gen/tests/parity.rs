@@ -0,0 +1,145 @@
+// Checks that `intrinsic_gen` renders the same intrinsic table as the
+// Python generator did, using `tests/expected-output` as the reference.
+
+extern crate intrinsic_gen;
+extern crate regex;
+
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::PathBuf;
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    inputs: String,
+    output: String,
+    llvm: String,
+}
+
+struct Table {
+    prefix: String,
+    entries: BTreeMap<String, Vec<Entry>>,
+}
+
+fn tests_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../tests")
+}
+
+fn read_table(source: &str) -> Table {
+    let prefix = Regex::new(r#"if !name\.starts_with\("([^"]*)"\)"#).unwrap();
+    let entry = Regex::new(concat!(
+        r#"(?s)"([^"]*)" => Intrinsic \{\s*"#,
+        r#"inputs: \{ static INPUTS: \[&'static Type; \d+\] = \[(.*?)\]; &INPUTS \},\s*"#,
+        r#"output: (.*?),\s*"#,
+        r#"definition: Named\("([^"]*)"\)\s*\},"#
+    )).unwrap();
+
+    let mut table = Table {
+        prefix: prefix.captures(source).map_or(String::new(), |c| c[1].to_string()),
+        entries: BTreeMap::new(),
+    };
+    for caps in entry.captures_iter(source) {
+        table.entries
+            .entry(caps[1].to_string())
+            .or_insert_with(Vec::new)
+            .push(Entry {
+                inputs: caps[2].to_string(),
+                output: caps[3].to_string(),
+                llvm: caps[4].to_string(),
+            });
+    }
+    table
+}
+
+fn check(input: &str, expected: &str) {
+    let generated = intrinsic_gen::parse(&tests_dir().join("input").join(input))
+        .and_then(|platform| intrinsic_gen::render(&platform))
+        .unwrap_or_else(|errors| {
+            let errors: Vec<_> = errors.iter().map(|e| e.to_string()).collect();
+            panic!("failed to generate {}:\n{}", input, errors.join("\n"))
+        });
+    let generated = read_table(&generated);
+
+    let mut source = String::new();
+    File::open(tests_dir().join("expected-output").join(expected))
+        .and_then(|mut f| f.read_to_string(&mut source))
+        .expect("failed to read expected output");
+    let expected = read_table(&source);
+
+    let mut report = vec![];
+    if generated.prefix != expected.prefix {
+        report.push(format!("platform prefix: expected `{}`, generated `{}`",
+                            expected.prefix,
+                            generated.prefix));
+    }
+    for (name, want) in &expected.entries {
+        let got = match generated.entries.get(name) {
+            Some(got) => got,
+            None => {
+                report.push(format!("missing: {}", name));
+                continue;
+            }
+        };
+        if got.len() != want.len() {
+            report.push(format!("{}: expected {} definitions, generated {}",
+                                name,
+                                want.len(),
+                                got.len()));
+        }
+        for (want, got) in want.iter().zip(got) {
+            if want.inputs != got.inputs || want.output != got.output {
+                report.push(format!("{}: signature differs\n    expected: [{}] -> {}\n    \
+                                     generated: [{}] -> {}",
+                                    name,
+                                    want.inputs,
+                                    want.output,
+                                    got.inputs,
+                                    got.output));
+            }
+            if want.llvm != got.llvm {
+                report.push(format!("{}: LLVM name differs\n    expected: {}\n    generated: {}",
+                                    name,
+                                    want.llvm,
+                                    got.llvm));
+            }
+        }
+    }
+    for name in generated.entries.keys() {
+        if !expected.entries.contains_key(name) {
+            report.push(format!("extra: {}", name));
+        }
+    }
+
+    if !report.is_empty() {
+        panic!("{} differences for {}:\n{}",
+               report.len(),
+               input,
+               report.join("\n"));
+    }
+}
+
+#[test]
+fn aarch64() {
+    check("aarch64.json", "aarch64.txt");
+}
+
+#[test]
+fn arm() {
+    check("arm.json", "arm.txt");
+}
+
+#[test]
+fn hexagon() {
+    check("hexagon", "hexagon.txt");
+}
+
+#[test]
+fn nvptx() {
+    check("nvptx", "nvptx.txt");
+}
+
+#[test]
+fn x86() {
+    check("x86", "x86.txt");
+}
@@ -0,0 +1,52 @@
+// Checks that `phf::build` gives every key its own slot, and that `index`
+// finds it there, and the tables `phf::tables` builds around it.
+
+extern crate intrinsic_gen;
+
+use intrinsic_gen::{phf, GenError};
+use std::path::Path;
+
+fn check(keys: &[&str]) {
+    let table = phf::build(keys);
+    let mut order = table.order.clone();
+    order.sort();
+    assert_eq!(order, (0..keys.len()).collect::<Vec<usize>>());
+    for (slot, &k) in table.order.iter().enumerate() {
+        assert_eq!(phf::index(keys[k], table.seed, &table.disps, keys.len()), slot);
+    }
+}
+
+#[test]
+fn single_key() {
+    check(&["mm_crc32_u8"]);
+}
+
+#[test]
+fn many_keys() {
+    let names: Vec<String> = (0..2000).map(|i| format!("mm256_op{}_epi8", i)).collect();
+    let keys: Vec<&str> = names.iter().map(|n| &n[..]).collect();
+    check(&keys);
+}
+
+#[test]
+fn tables() {
+    let intrinsics = [("b", "llvm.x"), ("a", "llvm.y"), ("b", "llvm.z")];
+    let tables = phf::tables(Path::new("toy.json"), &intrinsics).unwrap();
+    // the first `b` wins
+    assert_eq!(tables.entries, vec![1, 0]);
+    assert_eq!(tables.by_llvm_name, vec![1, 0]);
+    assert_eq!(tables.hash.unwrap().order.len(), 2);
+
+    assert!(phf::tables(Path::new("toy.json"), &[]).unwrap().hash.is_none());
+}
+
+#[test]
+fn too_many_keys() {
+    let names: Vec<String> = (0..phf::MAX_KEYS + 1).map(|i| format!("op{}", i)).collect();
+    let intrinsics: Vec<(&str, &str)> = names.iter().map(|n| (&n[..], "llvm.op")).collect();
+    match phf::tables(Path::new("toy.json"), &intrinsics) {
+        Err(GenError::TooManyIntrinsics { count, .. }) => assert_eq!(count, phf::MAX_KEYS + 1),
+        Err(e) => panic!("expected too many intrinsics, found {:?}", e),
+        Ok(_) => panic!("expected too many intrinsics"),
+    }
+}
@@ -0,0 +1,62 @@
+// Checks the instruction sets of a platform directory, as `build.rs` filters
+// them: which sets there are, which one each intrinsic comes from, and what
+// `retain_sets` leaves.
+
+extern crate intrinsic_gen;
+#[macro_use]
+extern crate serde_json;
+
+use intrinsic_gen::Platform;
+use std::path::Path;
+
+// A platform directory with `info.json` and the sets `a.json` and `b.json`.
+fn toy() -> Platform {
+    let info = json!({
+        "platform": "toy_",
+        "number_info": { "signed": {} },
+        "width_info": { "128": {} },
+    });
+    let set = |name: &str| {
+        json!({
+            "intrinsic_prefix": "",
+            "llvm_prefix": "llvm.toy.",
+            "intrinsics": [
+                { "intrinsic": name, "width": [128], "llvm": name, "ret": "s32", "args": ["0"] },
+            ],
+        })
+    };
+    let mut p = Platform::from_json(Path::new("toy/info.json"), &info).unwrap();
+    p.merge(Platform::from_json(Path::new("toy/a.json"), &set("a")).unwrap());
+    p.merge(Platform::from_json(Path::new("toy/b.json"), &set("b")).unwrap());
+    p
+}
+
+fn monomorphised(p: &Platform) -> Vec<(String, String)> {
+    p.monomorphise()
+        .unwrap()
+        .iter()
+        .map(|m| (m.name().to_string(), m.instruction_set().to_string()))
+        .collect()
+}
+
+#[test]
+fn instruction_sets() {
+    let p = toy();
+    // `info.json` defines no intrinsics, so it isn't a set
+    assert_eq!(p.instruction_sets(), vec!["a", "b"]);
+    assert_eq!(monomorphised(&p),
+               vec![("a".to_string(), "a".to_string()), ("b".to_string(), "b".to_string())]);
+}
+
+#[test]
+fn retain_sets() {
+    let mut p = toy();
+    p.retain_sets(|set| set != "a");
+    assert_eq!(p.instruction_sets(), vec!["b"]);
+    assert_eq!(monomorphised(&p), vec![("b".to_string(), "b".to_string())]);
+    assert_eq!(p.platform_prefix(), "toy_");
+
+    p.retain_sets(|_| false);
+    assert!(p.instruction_sets().is_empty());
+    assert!(monomorphised(&p).is_empty());
+}
@@ -0,0 +1,111 @@
+// Checks the `{N.prop}` patterns of intrinsic and LLVM names, both on their
+// own and as `Platform::monomorphise` resolves them.
+
+extern crate intrinsic_gen;
+#[macro_use]
+extern crate serde_json;
+
+use intrinsic_gen::parser::PlatformInfo;
+use intrinsic_gen::template::format;
+use intrinsic_gen::typespec::{type_info, NumKind, Number, PlatformTypeInfo, Type};
+use intrinsic_gen::Platform;
+use std::path::Path;
+
+fn info() -> PlatformInfo {
+    let json = json!({
+        "platform": "toy_",
+        "number_info": {
+            "signed": { "kind": "s", "data_type": { "pattern": "epi{bitwidth}" } },
+            "float": { "kind": "f", "data_type": { "32": "ps", "64": "pd" } },
+        },
+        "width_info": { "128": { "width_mm": "" }, "256": { "width_mm": "256" } },
+    });
+    PlatformInfo::from_json(Path::new("toy.json"), &json).unwrap().unwrap()
+}
+
+fn number(kind: NumKind, bitwidth: i32) -> Type {
+    Type::Num(Number {
+        kind: kind,
+        bitwidth: bitwidth,
+        llvm_bitwidth: None,
+    })
+}
+
+fn vector(kind: NumKind, bitwidth: i32, length: i32) -> Type {
+    Type::Vector {
+        elem: Box::new(number(kind, bitwidth)),
+        length: length,
+        bitcast: None,
+    }
+}
+
+fn type_infos(types: &[Type]) -> Vec<Option<PlatformTypeInfo>> {
+    types.iter().map(|t| type_info(t, &info()).unwrap()).collect()
+}
+
+fn render(template: &str, types: &[Type]) -> Result<String, String> {
+    let infos = type_infos(types);
+    let positional: Vec<Option<&PlatformTypeInfo>> = infos.iter().map(|i| i.as_ref()).collect();
+    format(template, &positional, &[("width", "256")])
+}
+
+#[test]
+fn placeholders() {
+    let types = [vector(NumKind::Float, 32, 8), vector(NumKind::Signed, 32, 8)];
+    assert_eq!(render("_mm{0.width_mm}_cvt{1.data_type}_{0.data_type}", &types).unwrap(),
+               "_mm256_cvtepi32_ps");
+    assert_eq!(render("llvm.toy.{0.kind}.{1.llvm_name}", &types).unwrap(),
+               "llvm.toy.f.v8i32");
+    assert_eq!(render("{{{width}}}", &types).unwrap(), "{256}");
+
+    let pair = Type::Aggregate {
+        flatten: false,
+        elems: vec![vector(NumKind::Signed, 32, 4), vector(NumKind::Signed, 32, 4)],
+    };
+    assert_eq!(render("ld2.{0[1].llvm_name}", &[pair]).unwrap(), "ld2.v4i32");
+}
+
+#[test]
+fn errors() {
+    let types = [Type::Void, vector(NumKind::Signed, 32, 4)];
+    assert_eq!(render("{0.kind}", &types).unwrap_err(),
+               "`{0.kind}` refers to a type without type information");
+    assert_eq!(render("{2.kind}", &types).unwrap_err(),
+               "`{2.kind}` refers to argument 2, but only 2 are known");
+    assert_eq!(render("{1.size}", &types).unwrap_err(),
+               "unknown property `size` in `{1.size}`");
+    assert_eq!(render("{1}", &types).unwrap_err(),
+               "`{1}` names the type v4i32, not one of its properties");
+    assert_eq!(render("{bits}", &types).unwrap_err(), "unknown name `bits` in `{bits}`");
+    assert_eq!(render("{1.kind", &types).unwrap_err(), "unterminated replacement field");
+    assert_eq!(render("1.kind}", &types).unwrap_err(), "single `}` encountered");
+}
+
+#[test]
+fn monomorphised_names() {
+    let json = json!({
+        "platform": "toy_",
+        "intrinsic_prefix": "_mm",
+        "llvm_prefix": "llvm.toy.",
+        "number_info": {
+            "signed": { "data_type": { "pattern": "epi{bitwidth}" } },
+            "unsigned": { "data_type": { "pattern": "epu{bitwidth}" } },
+        },
+        "width_info": { "128": { "width_mm": "" }, "256": { "width_mm": "256" } },
+        "intrinsics": [
+            { "intrinsic": "{0.width_mm}_abs_{0.data_type}", "width": [128, 256],
+              "llvm": "pabs.{0.llvm_name}", "ret": "s(8-16)", "args": ["0"] },
+        ],
+    });
+    let p = Platform::from_json(Path::new("toy.json"), &json).unwrap();
+    let names: Vec<(String, String)> = p.monomorphise()
+        .unwrap()
+        .iter()
+        .map(|m| (m.name().to_string(), m.llvm_name().to_string()))
+        .collect();
+    assert_eq!(names,
+               vec![("_mm_abs_epi8".to_string(), "llvm.toy.pabs.v16i8".to_string()),
+                    ("_mm_abs_epi16".to_string(), "llvm.toy.pabs.v8i16".to_string()),
+                    ("_mm256_abs_epi8".to_string(), "llvm.toy.pabs.v32i8".to_string()),
+                    ("_mm256_abs_epi16".to_string(), "llvm.toy.pabs.v16i16".to_string())]);
+}
@@ -0,0 +1,117 @@
+// Checks the properties `type_info` looks up in a platform's `number_info`
+// and `width_info` for each kind of type.
+
+extern crate intrinsic_gen;
+#[macro_use]
+extern crate serde_json;
+
+use intrinsic_gen::parser::PlatformInfo;
+use intrinsic_gen::typespec::{type_info, NumKind, Number, PlatformTypeInfo, Type};
+use std::path::Path;
+
+fn info() -> PlatformInfo {
+    let json = json!({
+        "platform": "toy_",
+        "number_info": {
+            "signed": {
+                "kind": "s",
+                "data_type": { "pattern": "epi{bitwidth}" },
+                "data_type_short": { "8": "b", "32": "d" },
+            },
+            "float": { "kind": "f", "data_type": { "32": "ps" } },
+        },
+        "width_info": { "128": { "width_suffix": "" }, "256": { "width_suffix": ".256" } },
+    });
+    PlatformInfo::from_json(Path::new("toy.json"), &json).unwrap().unwrap()
+}
+
+fn number(kind: NumKind, bitwidth: i32, llvm_bitwidth: Option<i32>) -> Type {
+    Type::Num(Number {
+        kind: kind,
+        bitwidth: bitwidth,
+        llvm_bitwidth: llvm_bitwidth,
+    })
+}
+
+fn vector(kind: NumKind, bitwidth: i32, length: i32) -> Type {
+    Type::Vector {
+        elem: Box::new(number(kind, bitwidth, None)),
+        length: length,
+        bitcast: None,
+    }
+}
+
+fn pointer(elem: Type, llvm_elem: Option<Type>) -> Type {
+    Type::Pointer {
+        elem: Box::new(elem),
+        llvm_elem: llvm_elem.map(Box::new),
+        is_const: true,
+    }
+}
+
+fn lookup(ty: &Type) -> PlatformTypeInfo {
+    type_info(ty, &info()).unwrap().unwrap()
+}
+
+#[test]
+fn numbers() {
+    let i32_info = lookup(&number(NumKind::Signed, 32, None));
+    assert_eq!(i32_info.llvm_name(), "i32");
+    assert_eq!(i32_info.get("kind"), Some("s"));
+    assert_eq!(i32_info.get("data_type"), Some("epi32"));
+    assert_eq!(i32_info.get("data_type_short"), Some("d"));
+
+    // no entry for the bitwidth and no pattern
+    let i16_info = lookup(&number(NumKind::Signed, 16, None));
+    assert_eq!(i16_info.get("data_type"), Some("epi16"));
+    assert_eq!(i16_info.get("data_type_short"), None);
+
+    assert_eq!(lookup(&number(NumKind::Signed, 32, Some(8))).llvm_name(), "i8");
+    assert_eq!(lookup(&number(NumKind::Float, 64, None)).get("data_type"), None);
+    // a kind without `number_info` only has an LLVM name
+    let u8_info = lookup(&number(NumKind::Unsigned, 8, None));
+    assert_eq!(u8_info.llvm_name(), "i8");
+    assert_eq!(u8_info.get("kind"), None);
+}
+
+#[test]
+fn vectors_and_pointers() {
+    let v8f32 = lookup(&vector(NumKind::Float, 32, 8));
+    assert_eq!(v8f32.llvm_name(), "v8f32");
+    assert_eq!(v8f32.get("data_type"), Some("ps"));
+    assert_eq!(v8f32.get("width_suffix"), Some(".256"));
+
+    match type_info(&vector(NumKind::Float, 32, 16), &info()) {
+        Err(e) => assert_eq!(e, "no width_info for vectors of width 512"),
+        Ok(info) => panic!("expected a missing width_info, found {:?}", info),
+    }
+
+    let ptr = lookup(&pointer(number(NumKind::Float, 32, None), None));
+    assert_eq!(ptr.llvm_name(), "p0f32");
+    assert_eq!(ptr.get("data_type"), Some("ps"));
+    let ptr = lookup(&pointer(vector(NumKind::Signed, 32, 4),
+                              Some(number(NumKind::Signed, 8, None))));
+    assert_eq!(ptr.llvm_name(), "p0i8");
+    assert_eq!(ptr.get("width_suffix"), Some(""));
+}
+
+#[test]
+fn aggregates_and_void() {
+    assert!(type_info(&Type::Void, &info()).unwrap().is_none());
+
+    let pair = Type::Aggregate {
+        flatten: false,
+        elems: vec![vector(NumKind::Signed, 8, 16), number(NumKind::Float, 32, None)],
+    };
+    let pair_info = lookup(&pair);
+    assert_eq!(pair_info.elem(0).unwrap().llvm_name(), "v16i8");
+    assert_eq!(pair_info.elem(1).unwrap().get("data_type"), Some("ps"));
+    assert!(pair_info.elem(2).is_none());
+
+    let with_void = Type::Aggregate {
+        flatten: false,
+        elems: vec![Type::Void],
+    };
+    assert_eq!(type_info(&with_void, &info()).unwrap_err(),
+               "aggregate element () has no type information");
+}
@@ -0,0 +1,123 @@
+// Exercises the type spec grammar on its own: every spec in `tests/input`
+// must print back unchanged, and malformed specs must point at the right
+// character.
+
+extern crate intrinsic_gen;
+extern crate serde_json;
+
+use intrinsic_gen::ast::{self, Core, Modifier, Suffix};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+fn input_files(path: &Path, files: &mut Vec<PathBuf>) {
+    if path.is_dir() {
+        for entry in fs::read_dir(path).unwrap() {
+            input_files(&entry.unwrap().path(), files);
+        }
+    } else if path.extension().map_or(false, |e| e == "json") {
+        files.push(path.to_owned());
+    }
+}
+
+fn collect_specs(json: &Value, specs: &mut Vec<String>) {
+    match *json {
+        Value::Object(ref map) => {
+            for (k, v) in map {
+                match (&k[..], v) {
+                    ("ret", &Value::String(ref s)) |
+                    ("args", &Value::String(ref s)) => specs.push(s.clone()),
+                    ("ret", &Value::Array(ref a)) |
+                    ("args", &Value::Array(ref a)) => {
+                        specs.extend(a.iter().filter_map(|s| s.as_str()).map(String::from))
+                    }
+                    _ => collect_specs(v, specs),
+                }
+            }
+        }
+        Value::Array(ref a) => {
+            for v in a {
+                collect_specs(v, specs);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn error_offset(spec: &str) -> usize {
+    match ast::parse(spec) {
+        Ok(parsed) => panic!("`{}` parsed as {:?}", spec, parsed),
+        Err(e) => e.offset,
+    }
+}
+
+#[test]
+fn round_trips_inputs() {
+    let mut files = vec![];
+    input_files(&PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../tests/input"),
+                &mut files);
+    let mut specs = vec![];
+    for file in files {
+        let mut source = String::new();
+        File::open(&file).unwrap().read_to_string(&mut source).unwrap();
+        collect_specs(&serde_json::from_str(&source).unwrap(), &mut specs);
+    }
+    assert!(!specs.is_empty());
+    for spec in specs {
+        match ast::parse(&spec) {
+            Ok(parsed) => assert_eq!(parsed.to_string(), spec),
+            Err(e) => panic!("failed to parse `{}`: {}", spec, e.reason),
+        }
+    }
+}
+
+#[test]
+fn nested_aggregates() {
+    let spec = ast::parse("((i32,0)f,[s8;2])").unwrap();
+    match spec.core {
+        Core::Tuple { ref elems, flatten: false } => {
+            assert_eq!(elems.len(), 2);
+            match elems[0].core {
+                Core::Tuple { ref elems, flatten: true } => assert_eq!(elems.len(), 2),
+                ref core => panic!("expected a flattened tuple, found {:?}", core),
+            }
+            match elems[1].core {
+                Core::Array { count: 2, .. } => {}
+                ref core => panic!("expected an array, found {:?}", core),
+            }
+        }
+        ref core => panic!("expected a tuple, found {:?}", core),
+    }
+    assert_eq!(spec.to_string(), "((i32,0)f,[s8;2])");
+}
+
+#[test]
+fn suffixes() {
+    let spec = ast::parse("0.1hx128->i16").unwrap();
+    assert_eq!(spec.modifiers.iter().map(|&(_, m)| m).collect::<Vec<_>>(),
+               vec![Modifier::Index(1), Modifier::Halve, Modifier::ForceWidth(128)]);
+    match spec.suffix {
+        Some((8, Suffix::Bitcast(ref target))) => assert_eq!(target.offset, 10),
+        ref suffix => panic!("expected a bitcast, found {:?}", suffix),
+    }
+
+    let spec = ast::parse("S32/8Pc/U8").unwrap();
+    match spec.suffix {
+        Some((5, Suffix::Pointer { is_const: true, llvm_elem: Some(ref elem) })) => {
+            assert_eq!(elem.to_string(), "U8")
+        }
+        ref suffix => panic!("expected a pointer, found {:?}", suffix),
+    }
+}
+
+#[test]
+fn error_offsets() {
+    assert_eq!(error_offset(""), 0);
+    assert_eq!(error_offset("0q"), 1);
+    assert_eq!(error_offset("i32Px"), 4);
+    assert_eq!(error_offset("s(8-"), 4);
+    assert_eq!(error_offset("(0,1"), 4);
+    assert_eq!(error_offset("[0.0;x]"), 5);
+    assert_eq!(error_offset("0->i32q"), 6);
+}
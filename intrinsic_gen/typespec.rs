@@ -1,460 +1,356 @@
-use regex::Regex;
-use regex::Captures;
-use serde_json::{self, Value};
-use std::collections::HashMap;
-use std::ascii::AsciiExt;
+use serde_json::Value;
 use std::collections::BTreeMap;
 use std::ops::Index;
+use ast::{self, Core, NumberId, Spec, Suffix};
 use parser::PlatformInfo;
+use template;
 use parser::WidthInfo;
 
-lazy_static! {
-    static ref SPEC: Regex = Regex::new(concat!(
-        r#"^(?:(?P<void>V)|(?P<id>[iusfIUSF])(?:\((?P<start>\d+)-(?P<end>\d+)\)|"#,
-        r#"(?P<width>\d+)(:?/(?P<llvm_width>\d+))?)"#,
-        r#"|(?P<reference>\d+))(?P<index>\.\d+)?(?P<modifiers>[vShdnwusfDMC]*)(?P<force_width>x\d+)?"#,
-        r#"(?:(?P<pointer>Pm|Pc)(?P<llvm_pointer>/.*)?|(?P<bitcast>->.*))?$"#
-    )).unwrap();
+// The generator's types are the owned model of the shared type crate.
+pub use intrinsic_types::{NumKind, Number};
+pub use intrinsic_types::OwnedType as Type;
+
+#[derive(Clone)]
+pub struct TypeSpec {
+    spec: Vec<(String, Spec)>,
 }
 
-lazy_static! {
-    static ref TYPE_ID_LOOKUP: HashMap<char, &'static [NumKind]> = {
-        static SU: &'static [NumKind] = &[NumKind::Signed, NumKind::Unsigned];
-        static S:  &'static [NumKind] = &[NumKind::Signed];
-        static U:  &'static [NumKind] = &[NumKind::Unsigned];
-        static F:  &'static [NumKind] = &[NumKind::Float];
-        let mut hm = HashMap::new();
-        hm.insert('i', SU);
-        hm.insert('s', S);
-        hm.insert('u', U);
-        hm.insert('f', F);
-        hm
-    };
+#[derive(Debug, Clone)]
+pub struct SpecError {
+    pub index: usize,
+    pub spec: String,
+    // byte offset of the offending character within `spec`
+    pub offset: usize,
+    pub reason: String,
+    pub help: Option<String>,
 }
 
-#[derive(Clone)]
-pub struct TypeSpec {
-    spec: Vec<String>,
+// A problem `offset` bytes into the spec currently being enumerated.
+struct Issue {
+    offset: usize,
+    reason: String,
+    help: Option<String>,
 }
 
-impl TypeSpec {
-    pub fn from_str(s: &str) -> TypeSpec {
-        let v = vec![s.to_string()];
-        TypeSpec { spec: v }
+impl Issue {
+    fn at(offset: usize, reason: String) -> Issue {
+        Issue {
+            offset: offset,
+            reason: reason,
+            help: None,
+        }
     }
 
-    pub fn from_list(v: &[String]) -> TypeSpec {
-        TypeSpec { spec: v.into() }
+    fn help(mut self, help: String) -> Issue {
+        self.help = Some(help);
+        self
     }
+}
 
-    pub fn enumerate(&self, width: i32, previous: &[Type]) -> Vec<Type> {
-
-        let mut result = vec![];
-        for spec in &self.spec {
-            println!("parsing spec {}", spec);
-            let caps = SPEC.captures(&spec);
-            if let Some(caps) = caps {
-                let id = caps.name("id");
-                let reference = caps.name("reference");
+impl TypeSpec {
+    pub fn from_str(s: &str) -> Result<TypeSpec, SpecError> {
+        TypeSpec::from_list(&[s.to_string()])
+    }
 
-                let mut modifiers: Vec<String> = vec![];
-                if let Some(index) = caps.name("index") {
-                    modifiers.push(index.as_str().to_string());
+    pub fn from_list(v: &[String]) -> Result<TypeSpec, SpecError> {
+        let mut spec = vec![];
+        for (index, s) in v.iter().enumerate() {
+            match ast::parse(s) {
+                Ok(parsed) => spec.push((s.clone(), parsed)),
+                Err(e) => {
+                    return Err(SpecError {
+                        index: index,
+                        spec: s.clone(),
+                        offset: e.offset,
+                        reason: e.reason,
+                        help: e.help,
+                    })
                 }
-                if let Some(m) = caps.name("modifiers") {
-                    for c in m.as_str().chars() {
-                        modifiers.push(c.to_string());
-                    }
-                }
-                if let Some(fw) = caps.name("force_width") {
-                    modifiers.push(fw.as_str().to_string());
-                }
-                if let Some(bc) = caps.name("bitcast") {
-                    modifiers.push(bc.as_str().to_string());
-                }
-
-                if let Some(v) = caps.name("void") {
-                    result.push(Type::Void);
-                } else if let Some(id) = id {
-                    let id = id.as_str().chars().next().unwrap();
-                    let is_vector = id.is_ascii_lowercase();
-                    let type_ctors = TYPE_ID_LOOKUP.get(&id.to_ascii_lowercase()).expect(
-                        &format!("find TYPE_ID {} failed", id.to_ascii_lowercase()));
-
-                    let (start, end, llvm_width);
-                    if let Some(s) = caps.name("start") {
-                        start = s.as_str();
-                        end = &caps["end"];
-                        llvm_width = None;
-                    } else {
-                        start = &caps["width"];
-                        end = &caps["width"];
-                        llvm_width = caps.name("llvm_width");
-                    }
-                    let start: i32 = start.parse().unwrap();
-                    let end: i32 = end.parse().unwrap();
-                    let mut bitwidth = start;
-                    while bitwidth <= end {
-                        for ctor in *type_ctors {
-                            let scalar = if let Some(llvm_width) = llvm_width {
-                                assert!(!is_vector);
-                                let llvm_width: i32 = llvm_width.as_str().parse().unwrap();
-                                assert!(llvm_width < bitwidth);
-                                Number {
-                                    kind: *ctor,
-                                    bitwidth: bitwidth,
-                                    llvm_bitwidth: Some(llvm_width),
-                                }
-                            } else {
-                                Number {
-                                    kind: *ctor,
-                                    bitwidth: bitwidth,
-                                    llvm_bitwidth: None,
-                                }
-                            };
-                            let mut elem = if is_vector {
-                                Type::Vector {
-                                    elem: Box::new(Type::Num(scalar)),
-                                    length: width / bitwidth,
-                                    bitcast: None,
-                                }
-                            } else {
-                                Type::Num(scalar)
-                            };
+            }
+        }
+        Ok(TypeSpec { spec: spec })
+    }
 
-                            for x in &modifiers {
-                                elem = elem.modify(&x, width, previous);
-                            }
-                            result.push(ptrify(&caps, elem, width, previous));
-                        }
-                        bitwidth *= 2;
-                    }
-                } else if let Some(reference) = reference {
-                    let reference: usize = reference.as_str().parse().unwrap();
-                    assert!(reference < previous.len(),
-                            format!("referring to argument {}, but only {} are known",
-                                    reference,
-                                    previous.len()));
-                    let mut ret = previous[reference].clone();
-                    for x in modifiers {
-                        ret = ret.modify(&x, width, previous);
-                    }
-                    result.push(ptrify(&caps, ret, width, &previous));
-                } else {
-                    panic!("matched `{}`, but didn\'t understand it?", spec);
+    pub fn enumerate(&self, width: i32, previous: &[Type]) -> Result<Vec<Type>, SpecError> {
+        let mut result = vec![];
+        for (index, &(ref s, ref spec)) in self.spec.iter().enumerate() {
+            match enumerate_spec(spec, width, previous) {
+                Ok(mut types) => result.append(&mut types),
+                Err(issue) => {
+                    return Err(SpecError {
+                        index: index,
+                        spec: s.clone(),
+                        offset: issue.offset,
+                        reason: issue.reason,
+                        help: issue.help,
+                    })
                 }
-            } else if spec.starts_with('(') {
-                let len = spec.len();
-                let (true_spec, flatten) = if spec.ends_with(')') {
-                    (&spec[1..len - 2], false)
-                } else if spec.ends_with(")f") {
-                    (&spec[1..len - 3], true)
-                } else {
-                    panic!("found unclosed aggregate {}", spec)
-                };
-            // TODO
-            } else if spec.starts_with('[') {
-                // TODO
-            } else {
-                panic!("Failed to parse {}", spec);
             }
         }
-        result
+        Ok(result)
     }
 }
 
-#[derive(Debug, Clone)]
-pub enum Type {
-    Void,
-    Num(Number),
-    Pointer {
-        elem: Box<Type>,
-        llvm_elem: Option<Box<Type>>,
-        is_const: bool,
-    },
-    Vector {
-        elem: Box<Type>,
-        length: i32,
-        bitcast: Option<Box<Type>>,
-    },
-    Aggregate { flatten: bool, elems: Vec<Type> },
-}
-
-#[derive(Debug, Copy, Clone)]
-enum NumKind {
-    Signed,
-    Unsigned,
-    Float,
-}
-
-#[derive(Debug, Copy, Clone)]
-pub struct Number {
-    kind: NumKind,
-    bitwidth: i32,
-    llvm_bitwidth: Option<i32>,
+fn enumerate_single(spec: &Spec, width: i32, previous: &[Type]) -> Result<Type, Issue> {
+    let mut choices = enumerate_spec(spec, width, previous)?;
+    if choices.len() != 1 {
+        return Err(Issue::at(spec.offset,
+                             format!("`{}` must describe exactly one type, found {}",
+                                     spec,
+                                     choices.len())));
+    }
+    Ok(choices.pop().unwrap())
 }
 
-impl Type {
-    pub fn bitwidth(&self) -> i32 {
-        match self {
-            &Type::Void => 0,
-            &Type::Num(ref n) => n.bitwidth,
-            _ => 0,
+// Modifiers apply left to right, then the pointer or bitcast suffix.
+fn enumerate_spec(spec: &Spec, width: i32, previous: &[Type]) -> Result<Vec<Type>, Issue> {
+    let mut result = vec![];
+    for mut ty in enumerate_core(spec, width, previous)? {
+        for &(offset, modifier) in &spec.modifiers {
+            ty = ty.modify(modifier, width).map_err(|reason| Issue::at(offset, reason))?;
         }
+        ty = match spec.suffix {
+            None => ty,
+            Some((offset, Suffix::Bitcast(ref target))) => {
+                let target = enumerate_single(target, width, previous)?;
+                ty.bitcast(target).map_err(|reason| Issue::at(offset, reason))?
+            }
+            Some((_, Suffix::Pointer { is_const, ref llvm_elem })) => {
+                let llvm_elem = match *llvm_elem {
+                    Some(ref elem) => Some(Box::new(enumerate_single(elem, width, previous)?)),
+                    None => None,
+                };
+                Type::Pointer {
+                    elem: Box::new(ty),
+                    llvm_elem: llvm_elem,
+                    is_const: is_const,
+                }
+            }
+        };
+        result.push(ty);
     }
+    Ok(result)
+}
 
-    pub fn compiler_ctor(&self) -> String {
-        match self {
-            &Type::Void => "::VOID".to_string(),
-            &Type::Num(ref n) => n.compiler_ctor(),
-            &Type::Pointer {
-                elem: ref e,
-                llvm_elem: ref le,
-                is_const: c,
-            } => {
-                let llvm_elem = if let &Some(ref le) = le {
-                    format!("Some({})", le.compiler_ctor_ref())
-                } else {
-                    "None".to_string()
-                };
-                format!("Type::Pointer({}, {}, {})",
-                        e.compiler_ctor_ref(),
-                        llvm_elem,
-                        c)
+fn enumerate_core(spec: &Spec, width: i32, previous: &[Type]) -> Result<Vec<Type>, Issue> {
+    let mut result = vec![];
+    match spec.core {
+        Core::Void => result.push(Type::Void),
+        Core::Number { id, vector, start, end, llvm_width } => {
+            let kinds: &[NumKind] = match id {
+                NumberId::Integer => &[NumKind::Signed, NumKind::Unsigned],
+                NumberId::Signed => &[NumKind::Signed],
+                NumberId::Unsigned => &[NumKind::Unsigned],
+                NumberId::Float => &[NumKind::Float],
+            };
+            let start = start as i32;
+            let end = end.map_or(start, |end| end as i32);
+            if start <= 0 || end < start {
+                return Err(Issue::at(spec.offset + 1,
+                                     format!("invalid bitwidth range {}-{}", start, end)));
             }
-            &Type::Vector {
-                elem: ref e,
-                length: l,
-                bitcast: ref bc,
-            } => {
-                if let &Some(ref bc) = bc {
-                    format!("{}x{}_{}",
-                            e.compiler_ctor(),
-                            l,
-                            bc.compiler_ctor().replace("::", ""))
-                } else {
-                    format!("{}x{}", e.compiler_ctor(), l)
+            // `id`, the width and `/`
+            let llvm_offset = spec.offset + 1 + start.to_string().len() + 1;
+            if let Some(llvm_width) = llvm_width {
+                if vector {
+                    return Err(Issue::at(llvm_offset,
+                                         "an LLVM width is only allowed on scalars".to_string())
+                        .help(format!("use `{}` for a scalar",
+                                      id.to_char(false))));
+                }
+                if llvm_width as i32 >= start {
+                    return Err(Issue::at(llvm_offset,
+                                         format!("LLVM width {} must be smaller than the width {}",
+                                                 llvm_width,
+                                                 start)));
                 }
             }
-            &Type::Aggregate {
-                flatten: f,
-                elems: ref e,
-            } => {
-                let parts = format!("{{ static PARTS: [&'static Type; {}] = [{}]; &PARTS }}",
-                                    e.len(),
-                                    e.iter()
-                                    .map(|ref x| x.compiler_ctor_ref())
-                                    .collect::<Vec<String>>()
-                                    .join(", "));
-                format!("Type::Aggregate({}, {})", f, parts)
+            let mut bitwidth = start;
+            while bitwidth <= end {
+                if vector && (width == 0 || width % bitwidth != 0) {
+                    let reason = if width == 0 {
+                        "vectors need a width, but the intrinsic only has width 0".to_string()
+                    } else {
+                        format!("a width of {} doesn't hold a whole number of {}-bit elements",
+                                width,
+                                bitwidth)
+                    };
+                    return Err(Issue::at(spec.offset, reason)
+                        .help(format!("use `{}` for a scalar", id.to_char(false))));
+                }
+                for kind in kinds {
+                    let scalar = Type::Num(Number {
+                        kind: *kind,
+                        bitwidth: bitwidth,
+                        llvm_bitwidth: llvm_width.map(|w| w as i32),
+                    });
+                    result.push(if vector {
+                        Type::Vector {
+                            elem: Box::new(scalar),
+                            length: width / bitwidth,
+                            bitcast: None,
+                        }
+                    } else {
+                        scalar
+                    });
+                }
+                bitwidth = match bitwidth.checked_mul(2) {
+                    Some(b) => b,
+                    None => break,
+                };
             }
         }
-    }
-
-    pub fn compiler_ctor_ref(&self) -> String {
-        let mut cc = self.compiler_ctor();
-        match self {
-            &Type::Pointer { .. } => format!("{{ static PTR: Type = {}; &PTR }}", cc),
-            &Type::Aggregate { .. } => format!("{{ static AGG: Type = {}; &AGG }}", cc),
-            _ => {
-                cc.insert(0, '&');
-                cc
+        Core::Reference(index) => {
+            if index >= previous.len() {
+                let help = if previous.is_empty() {
+                    "the return type cannot refer to other types".to_string()
+                } else if previous.len() == 1 {
+                    "only `0`, the return type, is known at this point".to_string()
+                } else {
+                    format!("references can only refer to the return type (`0`) and earlier \
+                             arguments (up to `{}`)",
+                            previous.len() - 1)
+                };
+                return Err(Issue::at(spec.offset,
+                                     format!("referring to argument {}, but only {} are known",
+                                             index,
+                                             previous.len()))
+                    .help(help));
             }
+            result.push(previous[index].clone());
         }
-    }
-
-    pub fn rust_name(&self) -> String {
-        match self {
-            &Type::Void => "()".to_string(),
-            &Type::Num(ref n) => n.rust_name(),
-            &Type::Pointer {
-                elem: ref e,
-                llvm_elem: ref le,
-                is_const: c,
-            } => {
-                let modifier = if c { "const" } else { "mut" };
-                format!("*{} {}", modifier, e.rust_name())
+        Core::Tuple { ref elems, flatten } => {
+            // Every element is enumerated independently: `(i32, i32)` covers
+            // all four combinations of signed and unsigned elements.
+            let mut choices: Vec<Vec<Type>> = vec![];
+            for elem in elems {
+                choices.push(enumerate_spec(elem, width, previous)?);
+            }
+            for elems in product(&choices) {
+                result.push(Type::Aggregate {
+                    flatten: flatten,
+                    elems: elems,
+                });
+            }
+        }
+        Core::Array { ref elem, count, flatten } => {
+            // Unlike `(...)`, all elements share a single enumerated type.
+            for elem in enumerate_spec(elem, width, previous)? {
+                result.push(Type::Aggregate {
+                    flatten: flatten,
+                    elems: vec![elem; count],
+                });
             }
-            &Type::Vector {
-                elem: ref e,
-                length: l,
-                bitcast: ref bc,
-            } => format!("{}x{}", e.rust_name(), l),
-            &Type::Aggregate {
-                flatten: f,
-                elems: ref e,
-            } => format!("({})", e.iter()
-                         .map(|ref x| x.rust_name())
-                         .collect::<Vec<String>>()
-                         .join(", "))
         }
     }
+    Ok(result)
+}
 
-    pub fn modify(self, spec: &str, width: i32, previous: &[Type]) -> Type {
-        match self {
-            Type::Void => self,
-            Type::Num(ref n) => {
-                match spec {
-                    "u" => Type::Num(Number {
-                        kind: NumKind::Unsigned,
-                        bitwidth: n.bitwidth,
-                        llvm_bitwidth: None,
-                    }),
-                    "s" => Type::Num(Number {
-                        kind: NumKind::Signed,
-                        bitwidth: n.bitwidth,
-                        llvm_bitwidth: None,
-                    }),
-                    "f" => Type::Num(Number {
-                        kind: NumKind::Float,
-                        bitwidth: n.bitwidth,
-                        llvm_bitwidth: None,
-                    }),
-                    "w" => Type::Num(Number {
-                        kind: n.kind,
-                        bitwidth: n.bitwidth * 2,
-                        llvm_bitwidth: None,
-                    }),
-                    "n" => Type::Num(Number {
-                        kind: n.kind,
-                        bitwidth: n.bitwidth / 2,
-                        llvm_bitwidth: None,
-                    }),
-                    "v" => Type::Vector {
-                        elem: Box::new(self.clone()),
-                        length: width / n.bitwidth,
-                        bitcast: None,
-                    },
-                    _ => panic!("unknown modification spec {}", spec),
-                }
-            }
-            Type::Pointer {
-                elem: e,
-                llvm_elem: le,
-                is_const: c,
-            } => {
-                match spec {
-                    "D" => *e,
-                    "M" => Type::Pointer {
-                        elem: e,
-                        llvm_elem: le,
-                        is_const: false,
-                    },
-                    "C" => Type::Pointer {
-                        elem: e,
-                        llvm_elem: le,
-                        is_const: true,
-                    },
-                    _ => Type::Pointer {
-                        elem: Box::new(e.modify(spec, width, previous)),
-                        llvm_elem: le,
-                        is_const: c,
-                    },
-                }
-            }
-            Type::Vector {
-                elem: e,
-                length: l,
-                bitcast: bc,
-            } => {
-                if spec == "S" {
-                    *e
-                } else if spec == "h" {
-                    Type::Vector {
-                        elem: e,
-                        length: l / 2,
-                        bitcast: None,
-                    }
-                } else if spec == "d" {
-                    Type::Vector {
-                        elem: e,
-                        length: l * 2,
-                        bitcast: None,
-                    }
-                } else if spec.starts_with('x') {
-                    let new_bitwidth: i32 = spec[1..].parse().expect("spec starts with 'x', but no integer followed");
-                    let bw = e.bitwidth();
-                    Type::Vector {
-                        elem: e,
-                        length: new_bitwidth / bw,
-                        bitcast: None,
-                    }
-                } else if spec.starts_with("->") {
-                    let bitcast_to = TypeSpec::from_str(&spec[2..]);
-                    let mut choices = bitcast_to.enumerate(width, previous);
-                    assert!(choices.len() == 1);
-                    Type::Vector {
-                        elem: e,
-                        length: l,
-                        bitcast: choices.pop().map(Box::new),
-                    }
-                } else {
-                    let elem = e.modify(spec, width, previous);
-                    Type::Vector {
-                        elem: Box::new(elem),
-                        length: l,
-                        bitcast: None,
+pub fn type_info(ty: &Type, platform_info: &PlatformInfo)
+                 -> Result<Option<PlatformTypeInfo>, String> {
+    Ok(match ty {
+        &Type::Void => None,
+        &Type::Num(ref n) => Some(number_type_info(n, platform_info)?),
+        &Type::Pointer {
+            elem: ref e,
+            llvm_elem: ref le,
+            ..
+        } => {
+            let llvm_elem = match le {
+                &Some(ref le) => type_info(le, platform_info)?,
+                &None => None,
+            };
+            type_info(e, platform_info)?.map(|info| info.pointer(llvm_elem.as_ref()))
+        }
+        &Type::Vector {
+            elem: ref e,
+            length: l,
+            ..
+        } => {
+            let bitwidth = ty.bitwidth().unwrap_or(0);
+            let width_info = platform_info.width_info(bitwidth)
+                .ok_or_else(|| format!("no width_info for vectors of width {}", bitwidth))?;
+            type_info(e, platform_info)?.map(|info| info.vectorize(l, width_info))
+        }
+        &Type::Aggregate { elems: ref e, .. } => {
+            let mut elems = vec![];
+            for x in e {
+                match type_info(x, platform_info)? {
+                    Some(info) => elems.push(info),
+                    None => {
+                        return Err(format!("aggregate element {} has no type information",
+                                           x.rust_name()))
                     }
                 }
             }
-            Type::Aggregate {
-                flatten: f,
-                elems: e,
-            } => {
-                if spec.starts_with('.') {
-                    let num: usize = spec[1..].parse().expect("spec starts with '.', but no integer followed");
-                    e[num].clone()
-                } else {
-                    unimplemented!()
-                }
-            }
+            Some(PlatformTypeInfo {
+                llvm_name: String::new(),
+                properties: BTreeMap::new(),
+                elems: elems,
+            })
         }
-    }
+    })
 }
 
-impl Number {
-    pub fn compiler_ctor(&self) -> String {
-        match self.kind {
-            NumKind::Signed => if let Some(lw) = self.llvm_bitwidth {
-                format!("::I{}_{}", self.bitwidth, lw)
-            } else {
-                format!("::I{}", self.bitwidth)
-            },
-            NumKind::Unsigned => if let Some(lw) = self.llvm_bitwidth {
-                format!("::U{}_{}", self.bitwidth, lw)
-            } else {
-                format!("::U{}", self.bitwidth)
-            },
-            NumKind::Float => format!("::F{}", self.bitwidth),
+fn number_type_info(n: &Number,
+                    platform_info: &PlatformInfo)
+                    -> Result<PlatformTypeInfo, String> {
+    let bitwidth = n.bitwidth.to_string();
+    let mut properties = BTreeMap::new();
+    if let Some(info) = platform_info.number_info(n.kind.name()) {
+        if let Value::Object(ref map) = info.props {
+            for (k, v) in map {
+                // Values are either plain strings, or maps keyed by bitwidth
+                // with an optional `pattern` fallback.
+                let prop = if !v.is_object() {
+                    property_string(v)
+                } else if let Some(v) = v.get(&bitwidth[..]) {
+                    property_string(v)
+                } else if let Some(&Value::String(ref pattern)) = v.get("pattern") {
+                    template::format(pattern, &[], &[("bitwidth", &bitwidth)])?
+                } else {
+                    continue;
+                };
+                properties.insert(k.clone(), prop);
+            }
         }
     }
-
-    pub fn rust_name(&self) -> String {
-        let m = match self.kind {
-            NumKind::Signed => 'i',
-            NumKind::Unsigned => 'u',
-            NumKind::Float => 'f',
-        };
-        format!("{}{}", m, self.bitwidth)
-    }
-
-    pub fn type_info(&self, platform_info: &PlatformInfo) -> PlatformTypeInfo {
-        unimplemented!()
-    }
+    Ok(PlatformTypeInfo {
+        llvm_name: n.llvm_name(),
+        properties: properties,
+        elems: vec![],
+    })
 }
 
+#[derive(Debug, Clone)]
 pub struct PlatformTypeInfo {
     llvm_name: String,
     properties: BTreeMap<String, String>,
-    elems: Vec<String>,
+    elems: Vec<PlatformTypeInfo>,
 }
 
 impl PlatformTypeInfo {
-    fn vectorize(self, length: i32, width_info: WidthInfo) -> PlatformTypeInfo {
+    pub fn llvm_name(&self) -> &str {
+        &self.llvm_name
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        if name == "llvm_name" {
+            Some(&self.llvm_name)
+        } else {
+            self.properties.get(name).map(|s| &s[..])
+        }
+    }
+
+    pub fn elem(&self, i: usize) -> Option<&PlatformTypeInfo> {
+        self.elems.get(i)
+    }
+
+    fn vectorize(self, length: i32, width_info: &WidthInfo) -> PlatformTypeInfo {
         let mut props = self.properties;
         if let Value::Object(ref map) = width_info.props {
             for (k, v) in map {
-                props.insert(k.to_string(), v.as_str().unwrap_or("").to_string());
+                props.insert(k.to_string(), property_string(v));
             }
         }
         PlatformTypeInfo {
@@ -487,32 +383,32 @@ impl Index<String> for PlatformTypeInfo {
 }
 
 impl Index<usize> for PlatformTypeInfo {
-    type Output = str;
+    type Output = PlatformTypeInfo;
 
-    fn index(&self, i: usize) -> &str {
+    fn index(&self, i: usize) -> &PlatformTypeInfo {
         &self.elems[i]
     }
 }
 
-fn ptrify(caps: &Captures, elem: Type, width: i32, previous: &[Type]) -> Type {
-    let ptr = caps.name("pointer");
-    if let Some(ptr) = ptr {
-        let llvm_elem = if let Some(llvm_ptr) = caps.name("llvm_pointer") {
-            assert!(llvm_ptr.as_str().starts_with('/'));
-            let mut options =
-                TypeSpec::from_str(&llvm_ptr.as_str()[1..]).enumerate(width, previous);
-            assert!(options.len() == 1);
-            Some(Box::new(options.pop().unwrap()))
-        } else {
-            None
-        };
-        assert!(ptr.as_str() == "Pc" || ptr.as_str() == "Pm");
-        return Type::Pointer {
-            elem: Box::new(elem),
-            llvm_elem: llvm_elem,
-            is_const: ptr.as_str() == "Pc",
-        };
-    } else {
-        return elem;
+fn product(choices: &[Vec<Type>]) -> Vec<Vec<Type>> {
+    let mut result = vec![vec![]];
+    for choice in choices {
+        let mut next = Vec::with_capacity(result.len() * choice.len());
+        for prefix in &result {
+            for x in choice {
+                let mut elems = prefix.clone();
+                elems.push(x.clone());
+                next.push(elems);
+            }
+        }
+        result = next;
+    }
+    result
+}
+
+fn property_string(v: &Value) -> String {
+    match *v {
+        Value::String(ref s) => s.clone(),
+        ref v => v.to_string(),
     }
 }
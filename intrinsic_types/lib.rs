@@ -0,0 +1,196 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The type model shared by `intrinsic_gen` and `rustc_platform_intrinsics`:
+// the `&'static` runtime `Type` of the generated tables, and the owned
+// `OwnedType` the generator builds from the specs. Both go through `repr` for
+// everything but construction; the runtime transformations only ever build
+// types out of the static tables.
+
+mod owned;
+mod repr;
+
+pub use owned::{Interner, Modifier, NumKind, Number, OwnedType};
+
+use repr::{Repr, View};
+use std::fmt;
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub enum Type {
+    Void,
+    Integer(/* signed */ bool, u8, /* llvm width */ u8),
+    Float(u8),
+    Pointer(&'static Type, Option<&'static Type>, /* const */ bool),
+    Vector(&'static Type, Option<&'static Type>, u16),
+    Aggregate(bool, &'static [&'static Type]),
+}
+
+// The scalars the transformations below can produce, whether or not the
+// enabled platforms use them.
+static SIGNED: [Type; 4] = [Type::Integer(true, 8, 8),
+                            Type::Integer(true, 16, 16),
+                            Type::Integer(true, 32, 32),
+                            Type::Integer(true, 64, 64)];
+static UNSIGNED: [Type; 4] = [Type::Integer(false, 8, 8),
+                              Type::Integer(false, 16, 16),
+                              Type::Integer(false, 32, 32),
+                              Type::Integer(false, 64, 64)];
+static FLOATS: [Type; 2] = [Type::Float(32), Type::Float(64)];
+
+// The entry of the tables above for `n`, if there is one.
+fn scalar(n: Number) -> Option<&'static Type> {
+    SIGNED.iter().chain(&UNSIGNED).chain(&FLOATS).find(|t| match t.view() {
+        View::Num(m) => m == n,
+        _ => false,
+    })
+}
+
+// The transformations are the spec modifiers of `OwnedType::modify`, and
+// `None` where it fails. They fail for more types, though: every scalar in
+// the result, on its own or as an element, has to be one of the tables
+// above, so e.g. `f32` doesn't narrow and `i64` doesn't widen, and vectors
+// have at most `u16::MAX` lanes.
+impl Type {
+    // The number of lanes of a vector.
+    pub fn lanes(&self) -> Option<u16> {
+        match self {
+            &Type::Vector(_, _, length) => Some(length),
+            _ => None,
+        }
+    }
+
+    // The element type of a vector (`S`).
+    pub fn element(&self) -> Option<&'static Type> {
+        match self {
+            &Type::Vector(elem, _, _) => Some(elem),
+            _ => None,
+        }
+    }
+
+    // The type a pointer points to (`D`).
+    pub fn pointee(&self) -> Option<&'static Type> {
+        match self {
+            &Type::Pointer(elem, _, _) => Some(elem),
+            _ => None,
+        }
+    }
+
+    // The width in bits of the whole value, e.g. 256 for `i8x32`; `None` for
+    // `()` and pointers.
+    pub fn bitwidth(&self) -> Option<u32> {
+        repr::bitwidth(self)
+    }
+
+    // Half as many lanes (`h`).
+    pub fn halve(&self) -> Option<Type> {
+        repr::modify(self, Modifier::Halve, 0).ok()
+    }
+
+    // Twice as many lanes (`d`).
+    pub fn double(&self) -> Option<Type> {
+        repr::modify(self, Modifier::Double, 0).ok()
+    }
+
+    // Elements twice as wide (`w`).
+    pub fn widen(&self) -> Option<Type> {
+        repr::modify(self, Modifier::Widen, 0).ok()
+    }
+
+    // Elements half as wide (`n`).
+    pub fn narrow(&self) -> Option<Type> {
+        repr::modify(self, Modifier::Narrow, 0).ok()
+    }
+
+    // Signed integer elements of the same width (`s`).
+    pub fn to_signed(&self) -> Option<Type> {
+        repr::modify(self, Modifier::Signed, 0).ok()
+    }
+
+    // Unsigned integer elements of the same width (`u`).
+    pub fn to_unsigned(&self) -> Option<Type> {
+        repr::modify(self, Modifier::Unsigned, 0).ok()
+    }
+
+    // Float elements of the same width (`f`).
+    pub fn to_float(&self) -> Option<Type> {
+        repr::modify(self, Modifier::Float, 0).ok()
+    }
+
+    // The LLVM IR spelling of a type, e.g. `<32 x i8>`, `float*` or
+    // `{ i32, i32 }`.
+    pub fn llvm_ir(&self) -> String {
+        repr::llvm_ir(self)
+    }
+}
+
+// The Rust spelling of a type, e.g. `i8x32`, `*const f32` or `(u8, u16)`.
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        repr::fmt_rust(self, f)
+    }
+}
+
+impl Repr for Type {
+    type Elem = &'static Type;
+    type Field = &'static Type;
+
+    fn view<'a>(&'a self) -> View<'a, Type> {
+        match self {
+            &Type::Void => View::Void,
+            &Type::Integer(signed, bitwidth, llvm_width) => {
+                View::Num(Number {
+                    kind: if signed { NumKind::Signed } else { NumKind::Unsigned },
+                    bitwidth: bitwidth as i32,
+                    llvm_bitwidth: if llvm_width != bitwidth {
+                        Some(llvm_width as i32)
+                    } else {
+                        None
+                    },
+                })
+            }
+            &Type::Float(bitwidth) => {
+                View::Num(Number {
+                    kind: NumKind::Float,
+                    bitwidth: bitwidth as i32,
+                    llvm_bitwidth: None,
+                })
+            }
+            &Type::Pointer(ref elem, ref llvm_elem, is_const) => {
+                View::Pointer(elem, llvm_elem.as_ref(), is_const)
+            }
+            &Type::Vector(ref elem, ref bitcast, length) => {
+                View::Vector(elem, bitcast.as_ref(), length as u32)
+            }
+            &Type::Aggregate(flatten, elems) => View::Aggregate(flatten, elems),
+        }
+    }
+
+    fn num(n: Number) -> Option<Type> {
+        scalar(n).cloned()
+    }
+
+    fn elem(ty: Type) -> Option<&'static Type> {
+        match ty.view() {
+            View::Num(n) => scalar(n),
+            _ => None,
+        }
+    }
+
+    fn pointer(elem: &'static Type, llvm_elem: Option<&'static Type>, is_const: bool) -> Type {
+        Type::Pointer(elem, llvm_elem, is_const)
+    }
+
+    fn vector(elem: &'static Type, bitcast: Option<&'static Type>, length: u32) -> Option<Type> {
+        if length > u16::max_value() as u32 {
+            return None;
+        }
+        Some(Type::Vector(elem, bitcast, length as u16))
+    }
+}
@@ -0,0 +1,473 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display, Formatter};
+
+use repr::{self, Repr, View};
+use Type;
+
+// The spec modifiers, e.g. `Widen` for `w`, as applied by `OwnedType::modify`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Modifier {
+    Vector,
+    Scalar,
+    Halve,
+    Double,
+    Narrow,
+    Widen,
+    Unsigned,
+    Signed,
+    Float,
+    Deref,
+    Mut,
+    Const,
+    ForceWidth(u32),
+    Index(usize),
+}
+
+impl Display for Modifier {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let c = match *self {
+            Modifier::Vector => 'v',
+            Modifier::Scalar => 'S',
+            Modifier::Halve => 'h',
+            Modifier::Double => 'd',
+            Modifier::Narrow => 'n',
+            Modifier::Widen => 'w',
+            Modifier::Unsigned => 'u',
+            Modifier::Signed => 's',
+            Modifier::Float => 'f',
+            Modifier::Deref => 'D',
+            Modifier::Mut => 'M',
+            Modifier::Const => 'C',
+            Modifier::ForceWidth(w) => return write!(f, "x{}", w),
+            Modifier::Index(i) => return write!(f, ".{}", i),
+        };
+        write!(f, "{}", c)
+    }
+}
+
+// The owned counterpart of the runtime `Type`, which the generator builds
+// from the specs and renders as `Type` constructors.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum OwnedType {
+    Void,
+    Num(Number),
+    Pointer {
+        elem: Box<OwnedType>,
+        llvm_elem: Option<Box<OwnedType>>,
+        is_const: bool,
+    },
+    Vector {
+        elem: Box<OwnedType>,
+        length: i32,
+        bitcast: Option<Box<OwnedType>>,
+    },
+    Aggregate { flatten: bool, elems: Vec<OwnedType> },
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum NumKind {
+    Signed,
+    Unsigned,
+    Float,
+}
+
+impl NumKind {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            NumKind::Signed => "signed",
+            NumKind::Unsigned => "unsigned",
+            NumKind::Float => "float",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Number {
+    pub kind: NumKind,
+    pub bitwidth: i32,
+    // only set when it differs from `bitwidth`
+    pub llvm_bitwidth: Option<i32>,
+}
+
+impl OwnedType {
+    // The width in bits of the whole value; `None` for `()` and pointers.
+    pub fn bitwidth(&self) -> Option<i32> {
+        repr::bitwidth(self).map(|b| b as i32)
+    }
+
+    pub fn compiler_ctor(&self) -> String {
+        match self {
+            &OwnedType::Void => "::VOID".to_string(),
+            &OwnedType::Num(ref n) => n.compiler_ctor(),
+            &OwnedType::Pointer {
+                elem: ref e,
+                llvm_elem: ref le,
+                is_const: c,
+            } => {
+                let llvm_elem = if let &Some(ref le) = le {
+                    format!("Some({})", le.compiler_ctor_ref())
+                } else {
+                    "None".to_string()
+                };
+                format!("Type::Pointer({}, {}, {})",
+                        e.compiler_ctor_ref(),
+                        llvm_elem,
+                        c)
+            }
+            &OwnedType::Vector {
+                elem: ref e,
+                length: l,
+                bitcast: ref bc,
+            } => {
+                if let &Some(ref bc) = bc {
+                    format!("{}x{}_{}",
+                            e.compiler_ctor(),
+                            l,
+                            bc.compiler_ctor().replace("::", ""))
+                } else {
+                    format!("{}x{}", e.compiler_ctor(), l)
+                }
+            }
+            &OwnedType::Aggregate {
+                flatten: f,
+                elems: ref e,
+            } => {
+                let parts = format!("{{ static PARTS: [&'static Type; {}] = [{}]; &PARTS }}",
+                                    e.len(),
+                                    e.iter()
+                                    .map(|ref x| x.compiler_ctor_ref())
+                                    .collect::<Vec<String>>()
+                                    .join(", "));
+                format!("Type::Aggregate({}, {})", f, parts)
+            }
+        }
+    }
+
+    pub fn compiler_ctor_ref(&self) -> String {
+        let mut cc = self.compiler_ctor();
+        match self {
+            &OwnedType::Pointer { .. } => format!("{{ static PTR: Type = {}; &PTR }}", cc),
+            &OwnedType::Aggregate { .. } => format!("{{ static AGG: Type = {}; &AGG }}", cc),
+            _ => {
+                cc.insert(0, '&');
+                cc
+            }
+        }
+    }
+
+    // The crate-level `static` that `compiler_ctor` refers to by name, as
+    // `(name, definition)`. Pointers and aggregates are built inline instead.
+    pub fn static_def(&self) -> Option<(String, String)> {
+        let name = self.compiler_ctor().trim_start_matches("::").to_string();
+        let def = match self {
+            &OwnedType::Void => "Type::Void".to_string(),
+            &OwnedType::Num(ref n) => {
+                match n.kind {
+                    NumKind::Signed | NumKind::Unsigned => {
+                        format!("Type::Integer({}, {}, {})",
+                                n.kind == NumKind::Signed,
+                                n.bitwidth,
+                                n.llvm_bitwidth.unwrap_or(n.bitwidth))
+                    }
+                    NumKind::Float => format!("Type::Float({})", n.bitwidth),
+                }
+            }
+            &OwnedType::Vector { elem: ref e, length: l, bitcast: ref bc } => {
+                let bitcast = match bc {
+                    &Some(ref bc) => format!("Some({})", bc.compiler_ctor_ref()),
+                    &None => "None".to_string(),
+                };
+                format!("Type::Vector({}, {}, {})", e.compiler_ctor_ref(), bitcast, l)
+            }
+            &OwnedType::Pointer { .. } |
+            &OwnedType::Aggregate { .. } => return None,
+        };
+        Some((name, def))
+    }
+
+    // Adds the statics needed by this type and every type it is built from.
+    pub fn collect_statics(&self, statics: &mut BTreeMap<String, String>) {
+        if let Some((name, def)) = self.static_def() {
+            statics.insert(name, def);
+        }
+        match self {
+            &OwnedType::Void |
+            &OwnedType::Num(_) => {}
+            &OwnedType::Pointer { elem: ref e, llvm_elem: ref le, .. } => {
+                e.collect_statics(statics);
+                if let &Some(ref le) = le {
+                    le.collect_statics(statics);
+                }
+            }
+            &OwnedType::Vector { elem: ref e, bitcast: ref bc, .. } => {
+                e.collect_statics(statics);
+                if let &Some(ref bc) = bc {
+                    bc.collect_statics(statics);
+                }
+            }
+            &OwnedType::Aggregate { elems: ref e, .. } => {
+                for x in e {
+                    x.collect_statics(statics);
+                }
+            }
+        }
+    }
+
+    pub fn rust_name(&self) -> String {
+        self.to_string()
+    }
+
+    // The LLVM IR spelling, e.g. `<32 x i8>`, `float*` or `{ i32, i32 }`.
+    pub fn llvm_ir(&self) -> String {
+        repr::llvm_ir(self)
+    }
+
+    pub fn bitcast(self, target: OwnedType) -> Result<OwnedType, String> {
+        match (self, target) {
+            (OwnedType::Vector { elem: e, length: l, .. },
+             OwnedType::Vector { elem: be, bitcast: None, .. }) => {
+                if be.bitwidth() != e.bitwidth() {
+                    return Err(format!("cannot bitcast {} elements to {}",
+                                       e.rust_name(),
+                                       be.rust_name()));
+                }
+                // Only the element type is recorded; bitcasting to the same
+                // element type is a no-op.
+                let bitcast = if be == e { None } else { Some(be) };
+                Ok(OwnedType::Vector {
+                    elem: e,
+                    length: l,
+                    bitcast: bitcast,
+                })
+            }
+            (OwnedType::Vector { .. }, target) => {
+                Err(format!("can only bitcast to a vector type, found {}", target.rust_name()))
+            }
+            (ty, _) => Err(format!("can only bitcast vectors, found {}", ty.rust_name())),
+        }
+    }
+
+    pub fn modify(self, modifier: Modifier, width: i32) -> Result<OwnedType, String> {
+        repr::modify(&self, modifier, width)
+    }
+}
+
+// The Rust spelling, e.g. `i8x32`.
+impl Display for OwnedType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        repr::fmt_rust(self, f)
+    }
+}
+
+impl Repr for OwnedType {
+    type Elem = Box<OwnedType>;
+    type Field = OwnedType;
+
+    fn view<'a>(&'a self) -> View<'a, OwnedType> {
+        match self {
+            &OwnedType::Void => View::Void,
+            &OwnedType::Num(n) => View::Num(n),
+            &OwnedType::Pointer { elem: ref e, llvm_elem: ref le, is_const: c } => {
+                View::Pointer(e, le.as_ref(), c)
+            }
+            &OwnedType::Vector { elem: ref e, length: l, bitcast: ref bc } => {
+                View::Vector(e, bc.as_ref(), l as u32)
+            }
+            &OwnedType::Aggregate { flatten: f, elems: ref e } => View::Aggregate(f, e),
+        }
+    }
+
+    fn num(n: Number) -> Option<OwnedType> {
+        Some(OwnedType::Num(n))
+    }
+
+    fn elem(ty: OwnedType) -> Option<Box<OwnedType>> {
+        Some(Box::new(ty))
+    }
+
+    fn pointer(elem: Box<OwnedType>,
+               llvm_elem: Option<Box<OwnedType>>,
+               is_const: bool)
+               -> OwnedType {
+        OwnedType::Pointer {
+            elem: elem,
+            llvm_elem: llvm_elem,
+            is_const: is_const,
+        }
+    }
+
+    fn vector(elem: Box<OwnedType>,
+              bitcast: Option<Box<OwnedType>>,
+              length: u32)
+              -> Option<OwnedType> {
+        Some(OwnedType::Vector {
+            elem: elem,
+            length: length as i32,
+            bitcast: bitcast,
+        })
+    }
+}
+
+impl Number {
+    pub fn compiler_ctor(&self) -> String {
+        match self.kind {
+            NumKind::Signed => if let Some(lw) = self.llvm_bitwidth {
+                format!("::I{}_{}", self.bitwidth, lw)
+            } else {
+                format!("::I{}", self.bitwidth)
+            },
+            NumKind::Unsigned => if let Some(lw) = self.llvm_bitwidth {
+                format!("::U{}_{}", self.bitwidth, lw)
+            } else {
+                format!("::U{}", self.bitwidth)
+            },
+            NumKind::Float => format!("::F{}", self.bitwidth),
+        }
+    }
+
+    pub fn rust_name(&self) -> String {
+        self.to_string()
+    }
+
+    pub fn llvm_ir(&self) -> String {
+        match (self.kind, self.bitwidth) {
+            (NumKind::Float, 16) => "half".to_string(),
+            (NumKind::Float, 32) => "float".to_string(),
+            (NumKind::Float, 64) => "double".to_string(),
+            _ => self.llvm_name(),
+        }
+    }
+
+    pub fn llvm_name(&self) -> String {
+        match self.kind {
+            NumKind::Signed | NumKind::Unsigned => {
+                format!("i{}", self.llvm_bitwidth.unwrap_or(self.bitwidth))
+            }
+            NumKind::Float => format!("f{}", self.bitwidth),
+        }
+    }
+}
+
+// The Rust spelling, e.g. `i8` or `f32`.
+impl Display for Number {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let m = match self.kind {
+            NumKind::Signed => 'i',
+            NumKind::Unsigned => 'u',
+            NumKind::Float => 'f',
+        };
+        write!(f, "{}{}", m, self.bitwidth)
+    }
+}
+
+impl<'a> From<&'a Type> for OwnedType {
+    fn from(ty: &'a Type) -> OwnedType {
+        match ty.view() {
+            View::Void => OwnedType::Void,
+            View::Num(n) => OwnedType::Num(n),
+            View::Pointer(&elem, llvm_elem, is_const) => {
+                OwnedType::Pointer {
+                    elem: Box::new(OwnedType::from(elem)),
+                    llvm_elem: llvm_elem.map(|&t| Box::new(OwnedType::from(t))),
+                    is_const: is_const,
+                }
+            }
+            View::Vector(&elem, bitcast, length) => {
+                OwnedType::Vector {
+                    elem: Box::new(OwnedType::from(elem)),
+                    length: length as i32,
+                    bitcast: bitcast.map(|&t| Box::new(OwnedType::from(t))),
+                }
+            }
+            View::Aggregate(flatten, elems) => {
+                OwnedType::Aggregate {
+                    flatten: flatten,
+                    elems: elems.iter().map(|&t| OwnedType::from(t)).collect(),
+                }
+            }
+        }
+    }
+}
+
+// Converts owned types to runtime ones, leaking each distinct type once. The
+// scalars Rust has come from the static tables instead.
+#[derive(Default)]
+pub struct Interner {
+    leaked: HashMap<OwnedType, &'static Type>,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    // The runtime type of `ty`, or `None` if a width doesn't fit.
+    pub fn intern(&mut self, ty: &OwnedType) -> Option<&'static Type> {
+        if let &OwnedType::Num(n) = ty {
+            if let Some(ty) = ::scalar(n) {
+                return Some(ty);
+            }
+        }
+        if let Some(&ty) = self.leaked.get(ty) {
+            return Some(ty);
+        }
+        let runtime = match ty {
+            &OwnedType::Void => Type::Void,
+            &OwnedType::Num(n) => {
+                let bitwidth = to_u8(n.bitwidth)?;
+                match n.kind {
+                    NumKind::Float => Type::Float(bitwidth),
+                    kind => {
+                        let llvm_width = to_u8(n.llvm_bitwidth.unwrap_or(n.bitwidth))?;
+                        Type::Integer(kind == NumKind::Signed, bitwidth, llvm_width)
+                    }
+                }
+            }
+            &OwnedType::Pointer { elem: ref e, llvm_elem: ref le, is_const: c } => {
+                let llvm_elem = match le {
+                    &Some(ref le) => Some(self.intern(le)?),
+                    &None => None,
+                };
+                Type::Pointer(self.intern(e)?, llvm_elem, c)
+            }
+            &OwnedType::Vector { elem: ref e, length: l, bitcast: ref bc } => {
+                let bitcast = match bc {
+                    &Some(ref bc) => Some(self.intern(bc)?),
+                    &None => None,
+                };
+                if l < 0 || l > u16::max_value() as i32 {
+                    return None;
+                }
+                Type::Vector(self.intern(e)?, bitcast, l as u16)
+            }
+            &OwnedType::Aggregate { flatten: f, elems: ref e } => {
+                let mut elems = vec![];
+                for x in e {
+                    elems.push(self.intern(x)?);
+                }
+                Type::Aggregate(f, leak(elems.into_boxed_slice()))
+            }
+        };
+        let leaked = leak(Box::new(runtime));
+        self.leaked.insert(ty.clone(), leaked);
+        Some(leaked)
+    }
+}
+
+fn to_u8(width: i32) -> Option<u8> {
+    if width >= 0 && width <= u8::max_value() as i32 { Some(width as u8) } else { None }
+}
+
+fn leak<T: ?Sized>(b: Box<T>) -> &'static T {
+    unsafe { &*Box::into_raw(b) }
+}
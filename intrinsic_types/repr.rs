@@ -0,0 +1,190 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The queries, renderings and spec modifiers of `Type` and `OwnedType`,
+// written once against a view of either that is one level deep.
+
+use std::borrow::Borrow;
+use std::fmt;
+
+use {Modifier, NumKind, Number};
+
+pub trait Repr: Clone + fmt::Display {
+    // What a pointer or vector holds its element as, and an aggregate its
+    // elements: `&'static Type` for `Type`, `Box<OwnedType>` and `OwnedType`
+    // for `OwnedType`.
+    type Elem: Borrow<Self> + Clone;
+    type Field: Borrow<Self>;
+
+    fn view<'a>(&'a self) -> View<'a, Self>;
+
+    // The constructors return `None` for a type the model can't hold.
+    fn num(n: Number) -> Option<Self>;
+    fn elem(ty: Self) -> Option<Self::Elem>;
+    fn pointer(elem: Self::Elem, llvm_elem: Option<Self::Elem>, is_const: bool) -> Self;
+    fn vector(elem: Self::Elem, bitcast: Option<Self::Elem>, length: u32) -> Option<Self>;
+}
+
+pub enum View<'a, T: Repr + 'a> {
+    Void,
+    Num(Number),
+    Pointer(&'a T::Elem, Option<&'a T::Elem>, /* const */ bool),
+    Vector(&'a T::Elem, Option<&'a T::Elem>, u32),
+    Aggregate(/* flatten */ bool, &'a [T::Field]),
+}
+
+// The width in bits of the whole value; `None` for `()` and pointers.
+pub fn bitwidth<T: Repr>(ty: &T) -> Option<u32> {
+    match ty.view() {
+        View::Void |
+        View::Pointer(..) => None,
+        View::Num(n) => Some(n.bitwidth as u32),
+        View::Vector(elem, _, length) => {
+            bitwidth::<T>(elem.borrow()).and_then(|b| b.checked_mul(length))
+        }
+        View::Aggregate(_, elems) => {
+            elems.iter().fold(Some(0), |sum, e| {
+                sum.and_then(|s| bitwidth::<T>(e.borrow()).and_then(|b| s.checked_add(b)))
+            })
+        }
+    }
+}
+
+// The Rust spelling, e.g. `i8x32`, `*const f32` or `(u8, u16)`.
+pub fn fmt_rust<T: Repr>(ty: &T, f: &mut fmt::Formatter) -> fmt::Result {
+    match ty.view() {
+        View::Void => write!(f, "()"),
+        View::Num(n) => write!(f, "{}", n),
+        View::Pointer(elem, _, is_const) => {
+            write!(f, "*{} ", if is_const { "const" } else { "mut" })?;
+            fmt_rust::<T>(elem.borrow(), f)
+        }
+        View::Vector(elem, _, length) => {
+            fmt_rust::<T>(elem.borrow(), f)?;
+            write!(f, "x{}", length)
+        }
+        View::Aggregate(_, elems) => {
+            write!(f, "(")?;
+            for (i, elem) in elems.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ", ")?;
+                }
+                fmt_rust::<T>(elem.borrow(), f)?;
+            }
+            write!(f, ")")
+        }
+    }
+}
+
+// The LLVM IR spelling, e.g. `<32 x i8>`, `float*` or `{ i32, i32 }`.
+// Flattened aggregates are a struct too; only a declaration's arguments pass
+// them as separate values.
+pub fn llvm_ir<T: Repr>(ty: &T) -> String {
+    match ty.view() {
+        View::Void => "void".to_string(),
+        View::Num(n) => n.llvm_ir(),
+        View::Pointer(elem, llvm_elem, _) => {
+            format!("{}*", llvm_ir::<T>(llvm_elem.unwrap_or(elem).borrow()))
+        }
+        View::Vector(elem, bitcast, length) => {
+            format!("<{} x {}>", length, llvm_ir::<T>(bitcast.unwrap_or(elem).borrow()))
+        }
+        View::Aggregate(_, elems) => {
+            let elems = elems.iter()
+                .map(|e| llvm_ir::<T>(e.borrow()))
+                .collect::<Vec<String>>()
+                .join(", ");
+            format!("{{ {} }}", elems)
+        }
+    }
+}
+
+// Applies a spec modifier, with `width` the width of the intrinsic for `v`.
+// Changing an element drops bitcasts and the llvm width.
+pub fn modify<T: Repr>(ty: &T, modifier: Modifier, width: i32) -> Result<T, String> {
+    let unsupported = || format!("`{}` cannot be applied to {}", modifier, ty);
+    let unrepresentable = || {
+        format!("`{}` on {} gives a type that can't be represented", modifier, ty)
+    };
+    let elem = |e: T| T::elem(e).ok_or_else(&unrepresentable);
+    match ty.view() {
+        View::Void => Err(unsupported()),
+        View::Num(n) => {
+            let (kind, bitwidth) = match modifier {
+                Modifier::Unsigned => (NumKind::Unsigned, n.bitwidth),
+                Modifier::Signed => (NumKind::Signed, n.bitwidth),
+                Modifier::Float => (NumKind::Float, n.bitwidth),
+                Modifier::Widen => (n.kind, n.bitwidth.checked_mul(2).ok_or_else(&unsupported)?),
+                Modifier::Narrow if n.bitwidth > 1 => (n.kind, n.bitwidth / 2),
+                Modifier::Vector if n.bitwidth > 0 => {
+                    let length = (width / n.bitwidth) as u32;
+                    return T::vector(elem(ty.clone())?, None, length)
+                        .ok_or_else(unrepresentable);
+                }
+                _ => return Err(unsupported()),
+            };
+            T::num(Number {
+                    kind: kind,
+                    bitwidth: bitwidth,
+                    llvm_bitwidth: None,
+                })
+                .ok_or_else(unrepresentable)
+        }
+        View::Pointer(e, llvm_elem, is_const) => {
+            match modifier {
+                Modifier::Deref => Ok(e.borrow().clone()),
+                Modifier::Mut => Ok(T::pointer(e.clone(), llvm_elem.cloned(), false)),
+                Modifier::Const => Ok(T::pointer(e.clone(), llvm_elem.cloned(), true)),
+                _ => {
+                    let e = elem(modify::<T>(e.borrow(), modifier, width)?)?;
+                    Ok(T::pointer(e, llvm_elem.cloned(), is_const))
+                }
+            }
+        }
+        View::Vector(e, _, length) => {
+            let (e, length) = match modifier {
+                Modifier::Scalar => return Ok(e.borrow().clone()),
+                Modifier::Halve if length % 2 == 0 => (e.clone(), length / 2),
+                Modifier::Halve => {
+                    return Err(format!("`{}` cannot be applied to {}, which has an odd number \
+                                        of lanes",
+                                       modifier,
+                                       ty))
+                }
+                Modifier::Double => {
+                    (e.clone(), length.checked_mul(2).ok_or_else(&unrepresentable)?)
+                }
+                Modifier::ForceWidth(new_bitwidth) => {
+                    match bitwidth::<T>(e.borrow()) {
+                        Some(bw) if bw > 0 => (e.clone(), new_bitwidth / bw),
+                        _ => {
+                            return Err(format!("`{}` cannot be applied to vectors of {}",
+                                               modifier,
+                                               e.borrow()))
+                        }
+                    }
+                }
+                _ => (elem(modify::<T>(e.borrow(), modifier, width)?)?, length),
+            };
+            T::vector(e, None, length).ok_or_else(unrepresentable)
+        }
+        View::Aggregate(_, elems) => {
+            match modifier {
+                Modifier::Index(i) if i < elems.len() => Ok(elems[i].borrow().clone()),
+                Modifier::Index(i) => {
+                    Err(format!("element {} requested, but the aggregate only has {}",
+                                i,
+                                elems.len()))
+                }
+                _ => Err(format!("`{}` cannot be applied to an aggregate", modifier)),
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// Checks the conversion from the runtime to the owned type model, and that
+// both render the same.
+
+extern crate intrinsic_types;
+
+use intrinsic_types::{Interner, Modifier, NumKind, Number, OwnedType, Type};
+use std::ptr;
+
+static I8: Type = Type::Integer(true, 8, 8);
+static I32_8: Type = Type::Integer(true, 32, 8);
+static I32: Type = Type::Integer(true, 32, 32);
+static F32: Type = Type::Float(32);
+static F64: Type = Type::Float(64);
+static I32X3: Type = Type::Vector(&I32, None, 3);
+static F64X2: Type = Type::Vector(&F64, None, 2);
+static I32X4_F32: Type = Type::Vector(&I32, Some(&F32), 4);
+static PARTS: [&'static Type; 2] = [&I32X4_F32, &I32_8];
+static AGG: Type = Type::Aggregate(true, &PARTS);
+static PTR: Type = Type::Pointer(&F32, Some(&I8), true);
+
+#[test]
+fn rendering() {
+    for ty in &[&Type::Void, &I8, &I32_8, &F32, &I32X4_F32, &AGG, &PTR] {
+        let owned = OwnedType::from(*ty);
+        assert_eq!(owned.rust_name(), ty.to_string());
+        assert_eq!(owned.llvm_ir(), ty.llvm_ir());
+    }
+    assert_eq!(AGG.to_string(), "(i32x4, i32)");
+    assert_eq!(AGG.llvm_ir(), "{ <4 x float>, i8 }");
+    assert_eq!(PTR.to_string(), "*const f32");
+    assert_eq!(PTR.llvm_ir(), "i8*");
+}
+
+#[test]
+fn compiler_ctors() {
+    assert_eq!(OwnedType::from(&I32_8).compiler_ctor(), "::I32_8");
+    assert_eq!(OwnedType::from(&I32X4_F32).compiler_ctor(), "::I32x4_F32");
+    assert_eq!(OwnedType::from(&PTR).compiler_ctor(),
+               "Type::Pointer(&::F32, Some(&::I8), true)");
+    assert_eq!(OwnedType::from(&I32X4_F32).static_def(),
+               Some(("I32x4_F32".to_string(), "Type::Vector(&::I32, Some(&::F32), 4)".to_string())));
+}
+
+// The runtime counterparts of the spec modifiers; `v`, `x`, `M`, `C` and
+// `.N` have none.
+static TRANSFORMS: [(Modifier, fn(&Type) -> Option<Type>); 9] = [
+    (Modifier::Halve, Type::halve),
+    (Modifier::Double, Type::double),
+    (Modifier::Widen, Type::widen),
+    (Modifier::Narrow, Type::narrow),
+    (Modifier::Signed, Type::to_signed),
+    (Modifier::Unsigned, Type::to_unsigned),
+    (Modifier::Float, Type::to_float),
+    (Modifier::Scalar, element),
+    (Modifier::Deref, pointee),
+];
+
+fn element(ty: &Type) -> Option<Type> {
+    ty.element().cloned()
+}
+
+fn pointee(ty: &Type) -> Option<Type> {
+    ty.pointee().cloned()
+}
+
+// Whether `ty` has a scalar that isn't among the runtime's `i8` to `u64`,
+// `f32` and `f64`.
+fn unusual_scalar(ty: &OwnedType) -> bool {
+    match ty {
+        &OwnedType::Void => false,
+        &OwnedType::Num(ref n) => {
+            let widths: &[i32] = match n.kind {
+                NumKind::Float => &[32, 64],
+                _ => &[8, 16, 32, 64],
+            };
+            !widths.contains(&n.bitwidth) || n.llvm_bitwidth.is_some()
+        }
+        &OwnedType::Pointer { elem: ref e, .. } |
+        &OwnedType::Vector { elem: ref e, .. } => unusual_scalar(e),
+        &OwnedType::Aggregate { elems: ref e, .. } => e.iter().any(unusual_scalar),
+    }
+}
+
+#[test]
+fn modifiers_match() {
+    // the runtime transformations are the spec modifiers, except that they
+    // only produce the scalars Rust has
+    for ty in &[&Type::Void, &I8, &I32, &F32, &F64, &I32X3, &I32X4_F32, &F64X2, &AGG, &PTR] {
+        for &(modifier, transform) in TRANSFORMS.iter() {
+            let owned = OwnedType::from(*ty).modify(modifier, 0);
+            match (transform(ty), owned) {
+                (Some(runtime), Ok(owned)) => {
+                    assert_eq!(OwnedType::from(&runtime), owned, "`{}` on {}", modifier, ty)
+                }
+                (None, Ok(owned)) => {
+                    assert!(unusual_scalar(&owned), "`{}` on {} gives {}", modifier, ty, owned)
+                }
+                (None, Err(_)) => {}
+                (Some(runtime), Err(e)) => {
+                    panic!("`{}` on {} gives {} at runtime, but fails with {}",
+                           modifier,
+                           ty,
+                           runtime,
+                           e)
+                }
+            }
+        }
+    }
+    assert_eq!(F32.to_signed(), Some(I32.clone()));
+    assert_eq!(F32.narrow(), None);
+    assert_eq!(OwnedType::from(&F32).modify(Modifier::Narrow, 0).unwrap().rust_name(), "f16");
+    assert_eq!(I32X3.halve(), None);
+    assert_eq!(OwnedType::from(&I32X3).modify(Modifier::Halve, 0).unwrap_err(),
+               "`h` cannot be applied to i32x3, which has an odd number of lanes");
+
+    let owned = OwnedType::from(&I32X4_F32);
+    assert_eq!(owned.bitwidth(), Some(128));
+    assert_eq!(I32X4_F32.bitwidth(), Some(128));
+    assert_eq!(OwnedType::from(&PTR).bitwidth(), None);
+}
+
+#[test]
+fn interning() {
+    let mut interner = Interner::new();
+    let ptr = interner.intern(&OwnedType::from(&PTR)).unwrap();
+    assert_eq!(ptr, &PTR);
+    // each distinct type is leaked once, and the scalars Rust has not at all
+    assert!(ptr::eq(interner.intern(&OwnedType::from(&PTR)).unwrap(), ptr));
+    assert!(ptr::eq(ptr.pointee().unwrap(), interner.intern(&OwnedType::from(&F32)).unwrap()));
+    let agg = interner.intern(&OwnedType::from(&AGG)).unwrap();
+    assert_eq!(agg, &AGG);
+    assert!(ptr::eq(interner.intern(&OwnedType::from(&AGG)).unwrap(), agg));
+
+    let wide = OwnedType::Num(Number {
+        kind: NumKind::Signed,
+        bitwidth: 512,
+        llvm_bitwidth: None,
+    });
+    assert_eq!(interner.intern(&wide), None);
+}
@@ -16,6 +16,23 @@
 #![deny(warnings)]
 #![allow(bad_style)]
 
+#[cfg(feature = "dynamic")]
+extern crate intrinsic_gen;
+extern crate intrinsic_types;
+
+use std::cmp::Ordering;
+use std::fmt;
+
+pub use intrinsic_types::Type;
+
+#[cfg(feature = "dynamic")]
+mod dynamic;
+#[cfg(feature = "dynamic")]
+pub use dynamic::{LoadError, Registry};
+#[cfg(feature = "dynamic")]
+pub use intrinsic_gen::{GenError, Platform};
+
+#[derive(Clone, Copy, Debug)]
 pub struct Intrinsic {
     pub inputs: &'static [&'static Type],
     pub output: &'static Type,
@@ -23,113 +40,412 @@ pub struct Intrinsic {
     pub definition: IntrinsicDef,
 }
 
-#[derive(Clone, Hash, Eq, PartialEq)]
-pub enum Type {
+#[derive(Clone, Copy, Debug)]
+pub enum IntrinsicDef {
+    Named(&'static str),
+}
+
+// A type in a user's `extern "platform-intrinsic"` declaration, to check
+// against the `Type` the intrinsic expects with `Intrinsic::check_signature`.
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub enum UserType {
     Void,
-    Integer(/* signed */ bool, u8, /* llvm width */ u8),
+    Integer(/* signed */ bool, u8),
     Float(u8),
-    Pointer(&'static Type, Option<&'static Type>, /* const */ bool),
-    Vector(&'static Type, Option<&'static Type>, u16),
-    Aggregate(bool, &'static [&'static Type]),
+    Pointer(Box<UserType>, /* const */ bool),
+    Vector(Box<UserType>, u16),
+    Tuple(Vec<UserType>),
 }
 
-pub enum IntrinsicDef {
-    Named(&'static str),
+impl fmt::Display for UserType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &UserType::Void => write!(f, "()"),
+            &UserType::Integer(signed, bitwidth) => {
+                write!(f, "{}{}", if signed { 'i' } else { 'u' }, bitwidth)
+            }
+            &UserType::Float(bitwidth) => write!(f, "f{}", bitwidth),
+            &UserType::Pointer(ref elem, is_const) => {
+                write!(f, "*{} {}", if is_const { "const" } else { "mut" }, elem)
+            }
+            &UserType::Vector(ref elem, length) => write!(f, "{}x{}", elem, length),
+            &UserType::Tuple(ref elems) => {
+                write!(f, "(")?;
+                for (i, elem) in elems.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", elem)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SignatureMismatch {
+    // The declaration takes `found` arguments instead of `expected`.
+    Arity { expected: usize, found: usize },
+    // Argument `argument`, or the return type for `None`, is `found` rather
+    // than `expected`.
+    Type {
+        argument: Option<usize>,
+        expected: &'static Type,
+        found: UserType,
+        kind: MismatchKind,
+    },
+}
+
+// The first difference between an expected and a found type, looking
+// through pointers, vectors and tuples.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MismatchKind {
+    // e.g. an integer instead of a vector, or of a float
+    Kind,
+    Signedness,
+    Bitwidth,
+    LaneCount,
+    // `*mut` instead of `*const`, or the other way around
+    Constness,
+    TupleLength,
+}
+
+impl fmt::Display for SignatureMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &SignatureMismatch::Arity { expected, found } => {
+                write!(f, "expected {} arguments, found {}", expected, found)
+            }
+            &SignatureMismatch::Type { argument, expected, ref found, kind } => {
+                match argument {
+                    Some(i) => write!(f, "argument {}", i + 1)?,
+                    None => write!(f, "return type")?,
+                }
+                let kind = match kind {
+                    MismatchKind::Kind => "wrong kind of type",
+                    MismatchKind::Signedness => "wrong signedness",
+                    MismatchKind::Bitwidth => "wrong bitwidth",
+                    MismatchKind::LaneCount => "wrong number of lanes",
+                    MismatchKind::Constness => "wrong pointer mutability",
+                    MismatchKind::TupleLength => "wrong number of tuple elements",
+                };
+                write!(f, ": expected `{}`, found `{}` ({})", expected, found, kind)
+            }
+        }
+    }
+}
+
+// Where `found` differs from `expected`, using the Rust-facing bitwidth and
+// element types: the llvm width, vector bitcasts and pointer `llvm_elem`s
+// are invisible to the declaration.
+fn mismatch(expected: &Type, found: &UserType) -> Option<MismatchKind> {
+    match (expected, found) {
+        (&Type::Void, &UserType::Void) => None,
+        (&Type::Integer(signed, bitwidth, _), &UserType::Integer(s, b)) => {
+            if signed != s {
+                Some(MismatchKind::Signedness)
+            } else if bitwidth != b {
+                Some(MismatchKind::Bitwidth)
+            } else {
+                None
+            }
+        }
+        (&Type::Float(bitwidth), &UserType::Float(b)) => {
+            if bitwidth != b { Some(MismatchKind::Bitwidth) } else { None }
+        }
+        (&Type::Pointer(elem, _, is_const), &UserType::Pointer(ref e, c)) => {
+            if is_const != c { Some(MismatchKind::Constness) } else { mismatch(elem, e) }
+        }
+        (&Type::Vector(elem, _, length), &UserType::Vector(ref e, l)) => {
+            if length != l { Some(MismatchKind::LaneCount) } else { mismatch(elem, e) }
+        }
+        (&Type::Aggregate(_, elems), &UserType::Tuple(ref es)) => {
+            if elems.len() != es.len() {
+                return Some(MismatchKind::TupleLength);
+            }
+            elems.iter().zip(es).filter_map(|(elem, e)| mismatch(elem, e)).next()
+        }
+        _ => Some(MismatchKind::Kind),
+    }
+}
+
+// One step of `Intrinsic::lowering_plan`. Arguments are converted from their
+// Rust to their LLVM representation, the return value the other way around.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LoweringStep {
+    // The argument, or the return value for `None`.
+    pub argument: Option<usize>,
+    // The tuple element the step applies to, e.g. `[1]` for the second
+    // element of a flattened or repacked tuple, `[]` for the whole value.
+    pub path: Vec<usize>,
+    pub op: LoweringOp,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LoweringOp {
+    // Bitcasts a vector to or from a vector of the same length of the given
+    // element type, e.g. `<4 x i32>` to `<4 x float>`.
+    Bitcast(&'static Type),
+    // Casts a pointer to or from a pointer to the given type.
+    PointerCast(&'static Type),
+    // Truncates an integer to the given width.
+    Truncate(u8),
+    // Sign or zero extends an integer to the given width.
+    Extend(/* signed */ bool, u8),
+    // Passes the elements of a tuple as that many separate arguments.
+    Flatten(usize),
+    // Moves the elements of a tuple to or from an LLVM struct.
+    Repack,
 }
 
-static I8: Type = Type::Integer(true, 8, 8);
-static I16: Type = Type::Integer(true, 16, 16);
-static I32: Type = Type::Integer(true, 32, 32);
-static I64: Type = Type::Integer(true, 64, 64);
-static U8: Type = Type::Integer(false, 8, 8);
-static U16: Type = Type::Integer(false, 16, 16);
-static U32: Type = Type::Integer(false, 32, 32);
-static U64: Type = Type::Integer(false, 64, 64);
-static F32: Type = Type::Float(32);
-static F64: Type = Type::Float(64);
-
-static I32_8: Type = Type::Integer(true, 32, 8);
-
-static I8x8: Type = Type::Vector(&I8, None, 8);
-static U8x8: Type = Type::Vector(&U8, None, 8);
-static I8x16: Type = Type::Vector(&I8, None, 16);
-static U8x16: Type = Type::Vector(&U8, None, 16);
-static I8x32: Type = Type::Vector(&I8, None, 32);
-static U8x32: Type = Type::Vector(&U8, None, 32);
-static I8x64: Type = Type::Vector(&I8, None, 64);
-static U8x64: Type = Type::Vector(&U8, None, 64);
-static I8x128: Type = Type::Vector(&I8, None, 128);
-static U8x128: Type = Type::Vector(&U8, None, 128);
-static I8x256: Type = Type::Vector(&I8, None, 256);
-static U8x256: Type = Type::Vector(&U8, None, 256);
-
-static I16x4: Type = Type::Vector(&I16, None, 4);
-static U16x4: Type = Type::Vector(&U16, None, 4);
-static I16x8: Type = Type::Vector(&I16, None, 8);
-static U16x8: Type = Type::Vector(&U16, None, 8);
-static I16x16: Type = Type::Vector(&I16, None, 16);
-static U16x16: Type = Type::Vector(&U16, None, 16);
-static I16x32: Type = Type::Vector(&I16, None, 32);
-static U16x32: Type = Type::Vector(&U16, None, 32);
-static I16x64: Type = Type::Vector(&I16, None, 64);
-static U16x64: Type = Type::Vector(&U16, None, 64);
-static I16x128: Type = Type::Vector(&I16, None, 128);
-static U16x128: Type = Type::Vector(&U16, None, 128);
-
-static I32x2: Type = Type::Vector(&I32, None, 2);
-static U32x2: Type = Type::Vector(&U32, None, 2);
-static I32x4: Type = Type::Vector(&I32, None, 4);
-static U32x4: Type = Type::Vector(&U32, None, 4);
-static I32x8: Type = Type::Vector(&I32, None, 8);
-static U32x8: Type = Type::Vector(&U32, None, 8);
-static I32x16: Type = Type::Vector(&I32, None, 16);
-static U32x16: Type = Type::Vector(&U32, None, 16);
-static I32x32: Type = Type::Vector(&I32, None, 32);
-static U32x32: Type = Type::Vector(&U32, None, 32);
-static I32x64: Type = Type::Vector(&I32, None, 64);
-static U32x64: Type = Type::Vector(&U32, None, 64);
-
-static I64x1: Type = Type::Vector(&I64, None, 1);
-static U64x1: Type = Type::Vector(&U64, None, 1);
-static I64x2: Type = Type::Vector(&I64, None, 2);
-static U64x2: Type = Type::Vector(&U64, None, 2);
-static I64x4: Type = Type::Vector(&I64, None, 4);
-static U64x4: Type = Type::Vector(&U64, None, 4);
-
-static F32x2: Type = Type::Vector(&F32, None, 2);
-static F32x4: Type = Type::Vector(&F32, None, 4);
-static F32x8: Type = Type::Vector(&F32, None, 8);
-static F64x1: Type = Type::Vector(&F64, None, 1);
-static F64x2: Type = Type::Vector(&F64, None, 2);
-static F64x4: Type = Type::Vector(&F64, None, 4);
-
-static I32x4_F32: Type = Type::Vector(&I32, Some(&F32), 4);
-static I32x8_F32: Type = Type::Vector(&I32, Some(&F32), 8);
-static I64x2_F64: Type = Type::Vector(&I64, Some(&F64), 2);
-static I64x4_F64: Type = Type::Vector(&I64, Some(&F64), 4);
-
-static VOID: Type = Type::Void;
-
-mod x86;
-mod arm;
-mod aarch64;
-mod nvptx;
-mod hexagon;
+fn llvm_arguments(ty: &Type, args: &mut Vec<String>) {
+    match ty {
+        &Type::Aggregate(true, elems) => {
+            for &elem in elems {
+                llvm_arguments(elem, args);
+            }
+        }
+        _ => args.push(ty.llvm_ir()),
+    }
+}
+
+fn lower(ty: &'static Type,
+         argument: Option<usize>,
+         path: &mut Vec<usize>,
+         steps: &mut Vec<LoweringStep>) {
+    let op = match ty {
+        &Type::Integer(signed, bitwidth, llvm_width) if bitwidth != llvm_width => {
+            let (from, to) = if argument.is_some() {
+                (bitwidth, llvm_width)
+            } else {
+                (llvm_width, bitwidth)
+            };
+            if to < from { LoweringOp::Truncate(to) } else { LoweringOp::Extend(signed, to) }
+        }
+        &Type::Pointer(_, Some(llvm_elem), _) => LoweringOp::PointerCast(llvm_elem),
+        &Type::Vector(_, Some(bitcast), _) => LoweringOp::Bitcast(bitcast),
+        // only arguments can be flattened
+        &Type::Aggregate(true, elems) if argument.is_some() => LoweringOp::Flatten(elems.len()),
+        &Type::Aggregate(..) => LoweringOp::Repack,
+        _ => return,
+    };
+    steps.push(LoweringStep {
+        argument: argument,
+        path: path.clone(),
+        op: op,
+    });
+    if let &Type::Aggregate(_, elems) = ty {
+        for (i, &elem) in elems.iter().enumerate() {
+            path.push(i);
+            lower(elem, argument, path, steps);
+            path.pop();
+        }
+    }
+}
+
+// The scalar and vector types used by the generated modules, generated by
+// `build.rs` from `tests/input`.
+include!(concat!(env!("OUT_DIR"), "/types.rs"));
+
+// The platform modules and `Intrinsic::find_static`, generated by `build.rs`
+// from `tests/input`.
+include!(concat!(env!("OUT_DIR"), "/platforms.rs"));
+
+// The `(full name, Intrinsic)` pairs of `Intrinsic::iter` and friends, in
+// name order within each platform.
+#[derive(Clone)]
+pub struct Iter {
+    tables: std::vec::IntoIter<&'static [(&'static str, Intrinsic)]>,
+    current: std::slice::Iter<'static, (&'static str, Intrinsic)>,
+}
+
+impl Iterator for Iter {
+    type Item = (&'static str, Intrinsic);
+
+    fn next(&mut self) -> Option<(&'static str, Intrinsic)> {
+        loop {
+            if let Some(&(name, intrinsic)) = self.current.next() {
+                return Some((name, intrinsic));
+            }
+            match self.tables.next() {
+                Some(table) => self.current = table.iter(),
+                None => return None,
+            }
+        }
+    }
+}
 
 impl Intrinsic {
-    pub fn find(name: &str) -> Option<Intrinsic> {
-        if name.starts_with("x86_") {
-            x86::find(name)
-        } else if name.starts_with("arm_") {
-            arm::find(name)
-        } else if name.starts_with("aarch64_") {
-            aarch64::find(name)
-        } else if name.starts_with("nvptx_") {
-            nvptx::find(name)
-        } else if name.starts_with("Q6_") {
-            hexagon::find(name)
+    // The Rust signature, e.g. `fn(i8x32, i8x32) -> i8x32`.
+    pub fn signature(&self) -> String {
+        format!("fn({}) -> {}",
+                self.inputs.iter().map(|t| t.to_string()).collect::<Vec<String>>().join(", "),
+                self.output)
+    }
+
+    // Checks a user's declaration of the intrinsic, taking `inputs` and
+    // returning `output`, reporting every argument that doesn't match.
+    pub fn check_signature(&self,
+                           inputs: &[UserType],
+                           output: UserType)
+                           -> Result<(), Vec<SignatureMismatch>> {
+        let mut mismatches = vec![];
+        if inputs.len() != self.inputs.len() {
+            mismatches.push(SignatureMismatch::Arity {
+                expected: self.inputs.len(),
+                found: inputs.len(),
+            });
         } else {
-            None
+            for (i, (&expected, found)) in self.inputs.iter().zip(inputs).enumerate() {
+                if let Some(kind) = mismatch(expected, found) {
+                    mismatches.push(SignatureMismatch::Type {
+                        argument: Some(i),
+                        expected: expected,
+                        found: found.clone(),
+                        kind: kind,
+                    });
+                }
+            }
+        }
+        if let Some(kind) = mismatch(self.output, &output) {
+            mismatches.push(SignatureMismatch::Type {
+                argument: None,
+                expected: self.output,
+                found: output,
+                kind: kind,
+            });
+        }
+        if mismatches.is_empty() { Ok(()) } else { Err(mismatches) }
+    }
+
+    // The conversions a call needs between the Rust and LLVM types, for each
+    // argument in order and then the return value. A tuple's step comes
+    // before those of its elements.
+    pub fn lowering_plan(&self) -> Vec<LoweringStep> {
+        let mut steps = vec![];
+        for (i, &input) in self.inputs.iter().enumerate() {
+            lower(input, Some(i), &mut vec![], &mut steps);
+        }
+        lower(self.output, None, &mut vec![], &mut steps);
+        steps
+    }
+
+    // The LLVM declaration of the intrinsic, e.g.
+    // `declare <32 x i8> @llvm.x86.avx2.pabs.b(<32 x i8>)`. Flattened
+    // arguments are declared as their elements, while a flattened return
+    // value stays a struct.
+    pub fn llvm_declaration(&self) -> String {
+        let IntrinsicDef::Named(name) = self.definition;
+        let mut inputs = vec![];
+        for &input in self.inputs {
+            llvm_arguments(input, &mut inputs);
+        }
+        format!("declare {} @{}({})", self.output.llvm_ir(), name, inputs.join(", "))
+    }
+
+    // The intrinsic with the given full name, e.g. `x86_mm256_abs_epi8`, from
+    // the installed `Registry` if it defines one and else the generated tables.
+    pub fn find(name: &str) -> Option<Intrinsic> {
+        #[cfg(feature = "dynamic")]
+        {
+            if let Some(intrinsic) = Registry::installed().and_then(|r| r.find(name)) {
+                return Some(intrinsic);
+            }
+        }
+        Intrinsic::find_static(name)
+    }
+
+    // Every intrinsic of the enabled platforms, sorted by name. Like the
+    // other iterators, this only covers the generated tables; a `Registry`
+    // has its own.
+    pub fn iter() -> Iter {
+        iter_tables(PLATFORMS.iter().map(|&(_, table, _)| table).collect())
+    }
+
+    // The intrinsics of the platform with the given prefix, e.g. `x86` or
+    // `Q6_`.
+    pub fn iter_platform(prefix: &str) -> Iter {
+        iter_platform(&PLATFORMS, prefix)
+    }
+
+    // The intrinsics whose full name starts with `prefix`, e.g.
+    // `x86_mm256_`.
+    pub fn iter_prefix(prefix: &str) -> Iter {
+        iter_prefix(&PLATFORMS, prefix)
+    }
+
+    // The full names of the intrinsics defined as the LLVM intrinsic
+    // `llvm_name`, e.g. `llvm.x86.avx2.pabs.b`, sorted. Names defined by the
+    // installed `Registry` come from it.
+    pub fn find_by_llvm_name(llvm_name: &str) -> Vec<&'static str> {
+        let names = find_by_llvm_name(&PLATFORMS, llvm_name);
+        #[cfg(feature = "dynamic")]
+        let names = match Registry::installed() {
+            Some(registry) => {
+                let mut names: Vec<&'static str> = names.into_iter()
+                    .filter(|name| registry.find(name).is_none())
+                    .chain(registry.find_by_llvm_name(llvm_name))
+                    .collect();
+                names.sort();
+                names
+            }
+            None => names,
+        };
+        names
+    }
+}
+
+// A platform's prefix, its `(full name, Intrinsic)` pairs sorted by name, and
+// the indices of those sorted by LLVM name, as in `PLATFORMS`.
+type Table = (&'static str, &'static [(&'static str, Intrinsic)], &'static [(&'static str, u16)]);
+
+fn iter_tables(tables: Vec<&'static [(&'static str, Intrinsic)]>) -> Iter {
+    Iter {
+        tables: tables.into_iter(),
+        current: [].iter(),
+    }
+}
+
+fn iter_platform(platforms: &[Table], prefix: &str) -> Iter {
+    iter_tables(platforms.iter()
+        .filter(|&&(p, _, _)| p == prefix)
+        .map(|&(_, table, _)| table)
+        .collect())
+}
+
+fn iter_prefix(platforms: &[Table], prefix: &str) -> Iter {
+    iter_tables(platforms.iter()
+        .filter(|&&(p, _, _)| p.starts_with(prefix) || prefix.starts_with(p))
+        .map(|&(_, table, _)| {
+            let start = table.binary_search_by(|&(name, _)| name.cmp(prefix))
+                .unwrap_or_else(|i| i);
+            let len = table[start..]
+                .iter()
+                .take_while(|&&(name, _)| name.starts_with(prefix))
+                .count();
+            &table[start..start + len]
+        })
+        .collect())
+}
+
+fn find_by_llvm_name(platforms: &[Table], llvm_name: &str) -> Vec<&'static str> {
+    let mut names = vec![];
+    for &(_, table, by_llvm_name) in platforms {
+        // the first entry not before `llvm_name`
+        let start = by_llvm_name.binary_search_by(|&(l, _)| if l < llvm_name {
+                Ordering::Less
+            } else {
+                Ordering::Greater
+            })
+            .unwrap_err();
+        for &(l, i) in &by_llvm_name[start..] {
+            if l != llvm_name {
+                break;
+            }
+            names.push(table[i as usize].0);
         }
     }
+    names.sort();
+    names
 }
@@ -0,0 +1,86 @@
+// Checks the queries and transformations on the runtime `Type` against the
+// types the generated tables use. Needs the `x86` and `aarch64` features.
+
+#![feature(rustc_private)]
+#![cfg(all(feature = "x86", feature = "aarch64"))]
+
+extern crate rustc_platform_intrinsics;
+
+use rustc_platform_intrinsics::{Intrinsic, Type};
+
+fn ret(name: &str) -> &'static Type {
+    Intrinsic::find(name).unwrap().output
+}
+
+fn arg(name: &str, i: usize) -> &'static Type {
+    Intrinsic::find(name).unwrap().inputs[i]
+}
+
+#[test]
+fn queries() {
+    let i8x32 = ret("x86_mm256_abs_epi8");
+    assert_eq!(i8x32.lanes(), Some(32));
+    assert_eq!(i8x32.element(), Some(&Type::Integer(true, 8, 8)));
+    assert_eq!(i8x32.bitwidth(), Some(256));
+    assert_eq!(i8x32.pointee(), None);
+
+    let ptr = arg("aarch64_vld2_u8", 0);
+    assert_eq!(ptr.pointee(), Some(&Type::Integer(false, 8, 8)));
+    assert_eq!(ptr.bitwidth(), None);
+    assert_eq!(ret("aarch64_vld2_u8").bitwidth(), Some(128));
+}
+
+#[test]
+fn transformations() {
+    // `_mm256_cvtps_pd` widens a `f32x4`
+    let f32x4 = arg("x86_mm256_cvtps_pd", 0);
+    let f64x4 = ret("x86_mm256_cvtps_pd");
+    assert_eq!(f32x4.widen().as_ref(), Some(f64x4));
+    assert_eq!(f64x4.narrow().as_ref(), Some(f32x4));
+    assert_eq!(f32x4.double().and_then(|t| t.halve()).as_ref(), Some(f32x4));
+    assert_eq!(f32x4.to_signed().unwrap().to_string(), "i32x4");
+
+    // `_mm256_madd_epi16` adds pairs of widened `i16` lanes
+    let i16x16 = arg("x86_mm256_madd_epi16", 0);
+    assert_eq!(i16x16.widen().and_then(|t| t.halve()).as_ref(),
+               Some(ret("x86_mm256_madd_epi16")));
+    assert_eq!(i16x16.to_unsigned().and_then(|t| t.to_signed()).as_ref(), Some(i16x16));
+    assert_eq!(i16x16.halve().unwrap().to_string(), "i16x8");
+    assert_eq!(ret("x86_mm256_abs_epi8").element().unwrap().to_unsigned(),
+               Some(Type::Integer(false, 8, 8)));
+
+    // the llvm width and bitcasts are dropped
+    let mask = arg("x86_mm_mask_i64gather_ps", 3);
+    assert_eq!(mask.to_string(), "i32x4");
+    assert_eq!(mask.to_float().unwrap().to_string(), "f32x4");
+    assert_eq!(mask.to_signed(), Some(Type::Vector(&Type::Integer(true, 32, 32), None, 4)));
+    assert_eq!(arg("x86_mm_mask_i64gather_ps", 4).widen(),
+               Some(Type::Integer(true, 64, 64)));
+    assert_eq!(Type::Integer(true, 64, 64).widen(), None);
+    assert_eq!(Type::Integer(true, 8, 8).narrow(), None);
+}
+
+#[test]
+fn rejections() {
+    // lanes only change on vectors, and only by whole lanes
+    let ptr = arg("aarch64_vld2_u8", 0);
+    assert_eq!(ptr.halve(), None);
+    assert_eq!(ptr.double(), None);
+    assert_eq!(ptr.to_signed().unwrap().to_string(), "*const i8");
+    let i32x3 = Type::Vector(&Type::Integer(true, 32, 32), None, 3);
+    assert_eq!(i32x3.halve(), None);
+    assert_eq!(i32x3.double().unwrap().to_string(), "i32x6");
+    assert_eq!(Type::Vector(&Type::Float(32), None, 40000).double(), None);
+
+    // aggregates and `()` have no elements to transform
+    let pair = ret("aarch64_vld2_u8");
+    assert_eq!(pair.halve(), None);
+    assert_eq!(pair.widen(), None);
+    assert_eq!(pair.to_float(), None);
+    assert_eq!(Type::Void.narrow(), None);
+
+    // nor does anything produce a scalar Rust doesn't have
+    assert_eq!(Type::Float(32).narrow(), None);
+    assert_eq!(Type::Float(64).widen(), None);
+    assert_eq!(arg("x86_mm256_madd_epi16", 0).to_float(), None);
+}
@@ -0,0 +1,79 @@
+// Checks `Intrinsic::check_signature` against user declarations. Needs the
+// `x86` and `aarch64` features.
+
+#![feature(rustc_private)]
+#![cfg(all(feature = "x86", feature = "aarch64"))]
+
+extern crate rustc_platform_intrinsics;
+
+use rustc_platform_intrinsics::{Intrinsic, MismatchKind, SignatureMismatch, UserType};
+
+fn int(signed: bool, bitwidth: u8) -> UserType {
+    UserType::Integer(signed, bitwidth)
+}
+
+fn vector(elem: UserType, length: u16) -> UserType {
+    UserType::Vector(Box::new(elem), length)
+}
+
+fn pointer(elem: UserType, is_const: bool) -> UserType {
+    UserType::Pointer(Box::new(elem), is_const)
+}
+
+fn kinds(result: Result<(), Vec<SignatureMismatch>>) -> Vec<(Option<usize>, MismatchKind)> {
+    result.unwrap_err()
+        .into_iter()
+        .map(|m| match m {
+            SignatureMismatch::Type { argument, kind, .. } => (argument, kind),
+            m => panic!("unexpected {:?}", m),
+        })
+        .collect()
+}
+
+#[test]
+fn matching() {
+    let abs = Intrinsic::find("x86_mm256_abs_epi8").unwrap();
+    assert_eq!(abs.check_signature(&[vector(int(true, 8), 32)], vector(int(true, 8), 32)),
+               Ok(()));
+
+    // `i32_8` is an `i32` to Rust, but an `i8` to LLVM, and the `i32x4_f32`
+    // mask a vector of `i32`s bitcast to floats
+    let gather = Intrinsic::find("x86_mm_mask_i64gather_ps").unwrap();
+    let f32x4 = vector(UserType::Float(32), 4);
+    let args = [f32x4.clone(),
+                pointer(UserType::Float(32), true),
+                vector(int(true, 64), 2),
+                vector(int(true, 32), 4),
+                int(true, 32)];
+    assert_eq!(gather.check_signature(&args, f32x4), Ok(()));
+
+    let ld2 = Intrinsic::find("aarch64_vld2_u8").unwrap();
+    let u8x8 = vector(int(false, 8), 8);
+    assert_eq!(ld2.check_signature(&[pointer(int(false, 8), true)],
+                                   UserType::Tuple(vec![u8x8.clone(), u8x8])),
+               Ok(()));
+}
+
+#[test]
+fn mismatches() {
+    let abs = Intrinsic::find("x86_mm256_abs_epi8").unwrap();
+    let i8x32 = vector(int(true, 8), 32);
+    assert_eq!(abs.check_signature(&[], i8x32.clone()),
+               Err(vec![SignatureMismatch::Arity {
+                            expected: 1,
+                            found: 0,
+                        }]));
+    assert_eq!(kinds(abs.check_signature(&[vector(int(true, 8), 16)], vector(int(false, 8), 32))),
+               [(Some(0), MismatchKind::LaneCount), (None, MismatchKind::Signedness)]);
+    assert_eq!(kinds(abs.check_signature(&[vector(int(true, 16), 32)], int(true, 8))),
+               [(Some(0), MismatchKind::Bitwidth), (None, MismatchKind::Kind)]);
+
+    let ld2 = Intrinsic::find("aarch64_vld2_u8").unwrap();
+    let u8x8 = vector(int(false, 8), 8);
+    assert_eq!(kinds(ld2.check_signature(&[pointer(int(false, 8), false)],
+                                         UserType::Tuple(vec![u8x8]))),
+               [(Some(0), MismatchKind::Constness), (None, MismatchKind::TupleLength)]);
+
+    let message = abs.check_signature(&[i8x32], UserType::Void).unwrap_err()[0].to_string();
+    assert_eq!(message, "return type: expected `i8x32`, found `()` (wrong kind of type)");
+}
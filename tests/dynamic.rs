@@ -0,0 +1,94 @@
+// Checks `Registry` against the generated tables, and that an installed one
+// takes precedence in the `Intrinsic` lookups. Needs the default features and
+// `dynamic`.
+
+#![feature(rustc_private)]
+#![cfg(all(feature = "dynamic", feature = "x86", feature = "arm", feature = "aarch64",
+           feature = "nvptx", feature = "hexagon"))]
+
+extern crate rustc_platform_intrinsics;
+
+use rustc_platform_intrinsics::{GenError, Intrinsic, IntrinsicDef, LoadError, Registry};
+use std::path::Path;
+
+fn llvm_name(intrinsic: Intrinsic) -> &'static str {
+    let IntrinsicDef::Named(name) = intrinsic.definition;
+    name
+}
+
+fn load(dir: &str) -> Registry {
+    Registry::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join(dir)).unwrap()
+}
+
+#[test]
+fn same_as_generated() {
+    let registry = load("tests/input");
+    let generated: Vec<(&str, Intrinsic)> = Intrinsic::iter().collect();
+    let loaded: Vec<(&str, Intrinsic)> = registry.iter().collect();
+    assert_eq!(loaded.len(), generated.len());
+    for (&(name, a), &(loaded_name, b)) in generated.iter().zip(&loaded) {
+        assert_eq!(name, loaded_name);
+        assert_eq!(a.signature(), b.signature(), "`{}`", name);
+        assert_eq!(llvm_name(a), llvm_name(b), "`{}`", name);
+        assert_eq!(llvm_name(registry.find(name).unwrap()), llvm_name(a));
+    }
+    assert!(registry.find("x86_mm256_abs_epi8_").is_none());
+
+    assert_eq!(registry.iter_platform("nvptx").count(),
+               Intrinsic::iter_platform("nvptx").count());
+    assert_eq!(registry.iter_prefix("x86_mm256_abs").map(|(n, _)| n).collect::<Vec<_>>(),
+               Intrinsic::iter_prefix("x86_mm256_abs").map(|(n, _)| n).collect::<Vec<_>>());
+    assert_eq!(registry.find_by_llvm_name("llvm.x86.avx2.pabs.b"),
+               vec!["x86_mm256_abs_epi8"]);
+}
+
+#[test]
+fn installed() {
+    let registry = load("tests/dynamic-input");
+    assert_eq!(registry.iter().map(|(n, _)| n).collect::<Vec<_>>(),
+               vec!["nvptx_block_dim_x", "toy_hadd_ps"]);
+    assert!(Intrinsic::find("toy_hadd_ps").is_none());
+    registry.install();
+
+    let hadd = Intrinsic::find("toy_hadd_ps").unwrap();
+    assert_eq!(hadd.signature(), "fn(f32x4, f32x4) -> f32x4");
+    assert_eq!(llvm_name(hadd), "llvm.toy.hadd.ps");
+    assert_eq!(Intrinsic::find_by_llvm_name("llvm.toy.hadd.ps"), vec!["toy_hadd_ps"]);
+
+    // the registry's `nvptx_block_dim_x` shadows the generated one
+    let block_dim_x = Intrinsic::find("nvptx_block_dim_x").unwrap();
+    assert_eq!(llvm_name(block_dim_x), "llvm.nvvm.read.ptx.sreg.nctaid.x");
+    assert_eq!(Intrinsic::find_by_llvm_name("llvm.nvvm.read.ptx.sreg.nctaid.x"),
+               vec!["nvptx_block_dim_x", "nvptx_grid_dim_x"]);
+    assert!(Intrinsic::find_by_llvm_name("llvm.nvvm.read.ptx.sreg.ntid.x").is_empty());
+
+    // the rest still come from the generated tables
+    assert_eq!(llvm_name(Intrinsic::find("nvptx_block_dim_y").unwrap()),
+               "llvm.nvvm.read.ptx.sreg.ntid.y");
+    assert!(Intrinsic::find("x86_mm256_abs_epi8").is_some());
+    assert_eq!(Intrinsic::iter_platform("toy_").count(), 0);
+}
+
+#[test]
+fn errors() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/missing");
+    let errors = Registry::load(&dir).err().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path(), &*dir);
+    match errors[0] {
+        LoadError::Spec(GenError::Io { .. }) => {}
+        ref e => panic!("expected an I/O error, found {:?}", e),
+    }
+
+    // the generator accepts an `i512`, but `Type` only has 8 bits for widths
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/dynamic-unsupported");
+    let errors = Registry::load(&dir).err().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path(), &*dir.join("wide.json"));
+    match errors[0] {
+        LoadError::Unsupported { ref reason, .. } => {
+            assert_eq!(reason, "`wide_add` uses `i512`, which `Type` can't represent")
+        }
+        ref e => panic!("expected an unsupported type, found {:?}", e),
+    }
+}
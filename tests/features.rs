@@ -0,0 +1,25 @@
+// Checks that `Intrinsic::find` only knows the platforms and instruction sets
+// whose feature is enabled, with `RUSTC_PLATFORM_INTRINSICS_SETS` unset.
+
+#![feature(rustc_private)]
+
+extern crate rustc_platform_intrinsics;
+
+use rustc_platform_intrinsics::Intrinsic;
+
+#[test]
+fn platforms() {
+    assert_eq!(Intrinsic::find("arm_vabd_f32").is_some(), cfg!(feature = "arm"));
+    assert_eq!(Intrinsic::find("aarch64_vld2_u8").is_some(), cfg!(feature = "aarch64"));
+    assert_eq!(Intrinsic::find("nvptx_syncthreads").is_some(), cfg!(feature = "nvptx"));
+    assert_eq!(Intrinsic::find("Q6_R_vextract64").is_some(), cfg!(feature = "hexagon"));
+}
+
+#[test]
+fn instruction_sets() {
+    // every x86 intrinsic is in a set, which `x86` enables with the others
+    assert_eq!(Intrinsic::find("x86_mm256_abs_epi8").is_some(),
+               cfg!(any(feature = "x86", feature = "x86-avx2")));
+    assert_eq!(Intrinsic::find("x86_mm_lfence").is_some(),
+               cfg!(any(feature = "x86", feature = "x86-sse2")));
+}
@@ -0,0 +1,63 @@
+// Checks `Intrinsic::iter` and friends against `Intrinsic::find`. Needs the
+// default features, so that every platform is generated.
+
+#![feature(rustc_private)]
+#![cfg(all(feature = "x86", feature = "arm", feature = "aarch64", feature = "nvptx",
+           feature = "hexagon"))]
+
+extern crate rustc_platform_intrinsics;
+
+use rustc_platform_intrinsics::{Intrinsic, IntrinsicDef};
+
+fn llvm_name(i: Intrinsic) -> &'static str {
+    match i.definition {
+        IntrinsicDef::Named(name) => name,
+    }
+}
+
+#[test]
+fn iter_is_sorted_and_found() {
+    let all: Vec<(&str, Intrinsic)> = Intrinsic::iter().collect();
+    assert!(!all.is_empty());
+    for pair in all.windows(2) {
+        assert!(pair[0].0 < pair[1].0, "`{}` before `{}`", pair[0].0, pair[1].0);
+    }
+    for &(name, intrinsic) in &all {
+        assert_eq!(Intrinsic::find(name).map(llvm_name), Some(llvm_name(intrinsic)));
+    }
+}
+
+#[test]
+fn iter_platform() {
+    let x86: Vec<&str> = Intrinsic::iter_platform("x86").map(|(name, _)| name).collect();
+    let all: Vec<&str> = Intrinsic::iter()
+        .map(|(name, _)| name)
+        .filter(|name| name.starts_with("x86"))
+        .collect();
+    assert_eq!(x86, all);
+    assert_eq!(Intrinsic::iter_platform("x86_").count(), 0);
+    assert_eq!(Intrinsic::iter_platform("mips").count(), 0);
+}
+
+#[test]
+fn iter_prefix() {
+    for prefix in &["", "x", "x86_mm256_", "x86_mm256_abs_epi8", "Q6_", "aarch64_vld", "zzz"] {
+        let found: Vec<&str> = Intrinsic::iter_prefix(prefix).map(|(name, _)| name).collect();
+        let all: Vec<&str> = Intrinsic::iter()
+            .map(|(name, _)| name)
+            .filter(|name| name.starts_with(prefix))
+            .collect();
+        assert_eq!(found, all, "`{}`", prefix);
+    }
+    assert!(Intrinsic::iter_prefix("x86_mm256_").count() > 0);
+}
+
+#[test]
+fn find_by_llvm_name() {
+    for (name, intrinsic) in Intrinsic::iter() {
+        assert!(Intrinsic::find_by_llvm_name(llvm_name(intrinsic)).contains(&name));
+    }
+    assert_eq!(Intrinsic::find_by_llvm_name("llvm.ctpop.v8i8"),
+               ["aarch64_vcnt_s8", "aarch64_vcnt_u8", "arm_vcnt_s8", "arm_vcnt_u8"]);
+    assert!(Intrinsic::find_by_llvm_name("llvm.nope").is_empty());
+}
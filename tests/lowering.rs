@@ -0,0 +1,42 @@
+// Checks `Intrinsic::lowering_plan` on intrinsics using each kind of
+// conversion. Needs the `x86` and `aarch64` features.
+
+#![feature(rustc_private)]
+#![cfg(all(feature = "x86", feature = "aarch64"))]
+
+extern crate rustc_platform_intrinsics;
+
+use rustc_platform_intrinsics::{Intrinsic, LoweringOp, LoweringStep, Type};
+
+fn plan(name: &str) -> Vec<(Option<usize>, Vec<usize>, LoweringOp)> {
+    Intrinsic::find(name)
+        .unwrap()
+        .lowering_plan()
+        .into_iter()
+        .map(|LoweringStep { argument, path, op }| (argument, path, op))
+        .collect()
+}
+
+#[test]
+fn plain() {
+    assert_eq!(plan("x86_mm256_abs_epi8"), []);
+}
+
+#[test]
+fn casts() {
+    assert_eq!(plan("x86_mm_mask_i64gather_ps"),
+               [(Some(1), vec![], LoweringOp::PointerCast(&Type::Integer(true, 8, 8))),
+                (Some(3), vec![], LoweringOp::Bitcast(&Type::Float(32))),
+                (Some(4), vec![], LoweringOp::Truncate(8))]);
+}
+
+static U8: Type = Type::Integer(false, 8, 8);
+static U8X8: Type = Type::Vector(&U8, None, 8);
+
+#[test]
+fn aggregates() {
+    assert_eq!(plan("aarch64_vld2_u8"),
+               [(Some(0), vec![], LoweringOp::PointerCast(&U8X8)),
+                (None, vec![], LoweringOp::Repack)]);
+    assert_eq!(plan("aarch64_vqtbl2_s8"), [(Some(0), vec![], LoweringOp::Flatten(2))]);
+}
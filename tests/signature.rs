@@ -0,0 +1,64 @@
+// Checks the Rust rendering of the runtime `Type` against the signatures
+// `generator.py --format extern-block` prints, and the LLVM rendering against
+// LLVM's own declarations. Needs the `x86` and `aarch64` features.
+
+#![feature(rustc_private)]
+#![cfg(all(feature = "x86", feature = "aarch64"))]
+
+extern crate rustc_platform_intrinsics;
+
+use rustc_platform_intrinsics::{Intrinsic, IntrinsicDef, Type};
+
+static U8: Type = Type::Integer(false, 8, 8);
+static U8X8: Type = Type::Vector(&U8, None, 8);
+static PAIR: [&'static Type; 2] = [&U8X8, &U8X8];
+static FLAT_PAIR: Type = Type::Aggregate(true, &PAIR);
+static SWAP_ARGS: [&'static Type; 2] = [&FLAT_PAIR, &U8X8];
+
+fn signature(name: &str) -> String {
+    Intrinsic::find(name).unwrap().signature()
+}
+
+#[test]
+fn signatures() {
+    assert_eq!(signature("x86_mm256_abs_epi8"), "fn(i8x32) -> i8x32");
+    assert_eq!(signature("x86_mm_mask_i32gather_epi32"),
+               "fn(i32x4, *const i32, i32x4, i32x4, i32) -> i32x4");
+    assert_eq!(signature("aarch64_vld2_u8"), "fn(*const u8) -> (u8x8, u8x8)");
+}
+
+#[test]
+fn display_and_debug() {
+    let abs = Intrinsic::find("x86_mm256_abs_epi8").unwrap();
+    assert_eq!(abs.output.to_string(), "i8x32");
+    assert!(format!("{:?}", abs).contains("llvm.x86.avx2.pabs.b"));
+}
+
+#[test]
+fn llvm_declarations() {
+    let declaration = |name| Intrinsic::find(name).unwrap().llvm_declaration();
+    assert_eq!(declaration("x86_mm256_abs_epi8"),
+               "declare <32 x i8> @llvm.x86.avx2.pabs.b(<32 x i8>)");
+    // bitcast vector elements, pointers to `i8` and the llvm width of `i32_8`
+    assert_eq!(declaration("x86_mm_mask_i64gather_ps"),
+               "declare <4 x float> @llvm.x86.avx2.gather.q.ps(<4 x float>, i8*, <2 x i64>, \
+                <4 x float>, i8)");
+    // pointer `llvm_elem`, and an aggregate return
+    assert_eq!(declaration("aarch64_vld2_u8"),
+               "declare { <8 x i8>, <8 x i8> } @llvm.aarch64.neon.ld2.v8i8.p0v8i8(<8 x i8>*)");
+    // flattened aggregate arguments
+    assert_eq!(declaration("aarch64_vqtbl2_s8"),
+               "declare <8 x i8> @llvm.aarch64.neon.tbl2.v8i8(<16 x i8>, <16 x i8>, <8 x i8>)");
+}
+
+#[test]
+fn flattened_aggregate_return() {
+    // only arguments are flattened; LLVM returns the pair as a struct
+    let swap = Intrinsic {
+        inputs: &SWAP_ARGS,
+        output: &FLAT_PAIR,
+        definition: IntrinsicDef::Named("llvm.toy.swap.v8i8"),
+    };
+    assert_eq!(swap.llvm_declaration(),
+               "declare { <8 x i8>, <8 x i8> } @llvm.toy.swap.v8i8(<8 x i8>, <8 x i8>, <8 x i8>)");
+}